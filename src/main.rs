@@ -1,13 +1,15 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
-use std::io::{self, Write};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, mpsc};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 const RESET: &str = "\x1B[0m";
 const COLOR_TITLE: &str = "\x1B[1;36m";
@@ -15,9 +17,48 @@ const COLOR_ART: &str = "\x1B[38;5;213m";
 const COLOR_OPTION_KEY: &str = "\x1B[1;33m";
 const COLOR_OPTION_TEXT: &str = "\x1B[0;37m";
 const COLOR_PROMPT: &str = "\x1B[38;5;159m";
+const COLOR_ERROR: &str = "\x1B[1;31m";
 const XP_SMALL: u32 = 5;
 const XP_MEDIUM: u32 = 10;
+/// Every action name the journal records, which `[xp]` rewards are keyed by.
+const JOURNAL_ACTIONS: &[&str] = &[
+    "search_tombs",
+    "check_mail",
+    "computer",
+    "closet",
+    "explore",
+    "lay_down",
+    "screensaver",
+    "chest",
+    "pocket",
+    "custom",
+];
+const FAILURE_DAMAGE: u32 = 8;
 const VIEW_WIDTH: usize = 60;
+const XP_BAR_WIDTH: usize = 30;
+const SAVE_VERSION: u32 = 5;
+const START_LOCATION: &str = "town_square";
+const LEDGER_ROWS: usize = 15;
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+/// How long a stopped command gets between SIGTERM and SIGKILL.
+const TIMEOUT_GRACE: Duration = Duration::from_secs(5);
+/// How often `stream_command` checks on its child between lines.
+const STREAM_TICK: Duration = Duration::from_millis(100);
+/// How long `stream_command` waits for buffered output after its child exits.
+const STREAM_DRAIN: Duration = Duration::from_millis(500);
+/// How long a background or detached tool is watched after it starts; one
+/// that exits within it is judged by its exit status.
+const SPAWN_SETTLE: Duration = Duration::from_millis(300);
+/// How often `watch_config_files` checks the config layers for edits.
+const CONFIG_POLL: Duration = Duration::from_secs(1);
+const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const USAGE: &str = "Usage: lord [--config <path>] [--profile <name>] [--start <location>] [--no-splash] [--no-color]
+       lord version
+       lord journal [--since YYYY-MM-DD] [--until YYYY-MM-DD] [--action <name>] [--profile <name>]
+       lord run <action> [<name>]   (e.g. run mail, run chest \"Nmap Sweep\", run pocket \"Grin Wallet\")
+       lord init [--force]
+       lord check-config [<path>]
+       lord config show [--origin]";
 const SPLASH_ART: &str = r#"
  ___       _______   _______    _______  _____  ___   ________          ______    _______     
 |"  |     /"     "| /" _   "|  /"     "|(\"   \|"  \ |"      "\        /    " \  /"     "|    
@@ -71,38 +112,300 @@ const ROOM_ART: &str = r#"
 "#;
 
 fn main() {
-    let config = Config::load();
+    let cli = match Cli::parse(env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if cli.no_color || env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        COLOR_ENABLED.store(false, Ordering::Relaxed);
+    }
+    if let Some(path) = &cli.config {
+        if !path.is_file() {
+            eprintln!("--config: {} is not a readable file", path.display());
+            std::process::exit(2);
+        }
+        let _ = CONFIG_OVERRIDE.set(path.clone());
+    }
+    match &cli.command {
+        Some(CliCommand::Version) => {
+            println!("lord {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        Some(CliCommand::Help) => {
+            println!("{USAGE}");
+            return;
+        }
+        Some(CliCommand::Journal(query)) => {
+            std::process::exit(run_journal_command(query, cli.profile.as_deref()));
+        }
+        Some(CliCommand::CheckConfig(path)) => {
+            std::process::exit(run_check_config(path.as_deref()));
+        }
+        Some(CliCommand::ConfigShow { origin }) => {
+            std::process::exit(run_config_show(*origin));
+        }
+        Some(CliCommand::Init { force }) => {
+            std::process::exit(run_init(*force));
+        }
+        Some(CliCommand::Run(_)) | None => {}
+    }
+    let (config, config_problems) = Config::load();
     let mut game = Game::new(config);
-    if let Err(err) = game.run() {
+    if !config_problems.is_empty() {
+        game.notices
+            .insert(0, (config_problems.join("\n"), COLOR_ERROR));
+    }
+    if let Some(start) = &cli.start
+        && !game.world.iter().any(|def| &def.id == start)
+    {
+        let known: Vec<&str> = game.world.iter().map(|def| def.id.as_str()).collect();
+        eprintln!(
+            "--start: unknown location {start} (known: {})",
+            known.join(", ")
+        );
+        std::process::exit(2);
+    }
+    if let Some(CliCommand::Run(request)) = &cli.command {
+        for (notice, _) in game.notices.drain(..) {
+            eprintln!("{notice}");
+        }
+        let code = game.run_action(&cli, request).unwrap_or_else(|err| {
+            eprintln!("An error occurred: {err}");
+            1
+        });
+        std::process::exit(code);
+    }
+    if let Err(err) = game.run(&cli) {
         eprintln!("An error occurred: {err}");
     }
 }
 
+#[derive(Default)]
+struct Cli {
+    profile: Option<String>,
+    /// Read only this file instead of the usual config layers.
+    config: Option<PathBuf>,
+    /// Location to begin in, overriding the one saved in the profile.
+    start: Option<String>,
+    no_splash: bool,
+    no_color: bool,
+    command: Option<CliCommand>,
+}
+
+enum CliCommand {
+    Version,
+    Help,
+    Journal(JournalQuery),
+    CheckConfig(Option<PathBuf>),
+    ConfigShow { origin: bool },
+    Init { force: bool },
+    Run(RunRequest),
+}
+
+/// `lord run <action> [<name>]`: one action, no menus.
+#[derive(Default)]
+struct RunRequest {
+    action: String,
+    target: Option<String>,
+}
+
+#[derive(Default)]
+struct JournalQuery {
+    since: Option<String>,
+    until: Option<String>,
+    action: Option<String>,
+}
+
+impl Cli {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut cli = Cli::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{flag} requires a value"))
+            };
+            match (flag, cli.command.as_mut()) {
+                ("--profile", _) => cli.profile = Some(value()?),
+                ("--config", _) => cli.config = Some(PathBuf::from(value()?)),
+                ("--start", _) => cli.start = Some(value()?),
+                ("--no-splash", _) => cli.no_splash = true,
+                ("--no-color", _) => cli.no_color = true,
+                ("version" | "--version", None) => cli.command = Some(CliCommand::Version),
+                ("help" | "--help" | "-h", None) => cli.command = Some(CliCommand::Help),
+                ("journal", None) => {
+                    cli.command = Some(CliCommand::Journal(JournalQuery::default()))
+                }
+                ("--since", Some(CliCommand::Journal(query))) => {
+                    query.since = Some(parse_date_arg(flag, value()?)?)
+                }
+                ("--until", Some(CliCommand::Journal(query))) => {
+                    query.until = Some(parse_date_arg(flag, value()?)?)
+                }
+                ("--action", Some(CliCommand::Journal(query))) => query.action = Some(value()?),
+                ("check-config", None) => cli.command = Some(CliCommand::CheckConfig(None)),
+                ("config", None) => match args.next().as_deref() {
+                    Some("show") => cli.command = Some(CliCommand::ConfigShow { origin: false }),
+                    _ => return Err(String::from("config expects a subcommand: show")),
+                },
+                ("--origin", Some(CliCommand::ConfigShow { origin })) => *origin = true,
+                ("init", None) => cli.command = Some(CliCommand::Init { force: false }),
+                ("--force", Some(CliCommand::Init { force })) => *force = true,
+                (path, Some(CliCommand::CheckConfig(target @ None))) if !path.starts_with("--") => {
+                    *target = Some(PathBuf::from(path))
+                }
+                ("run", None) => cli.command = Some(CliCommand::Run(RunRequest::default())),
+                (word, Some(CliCommand::Run(request))) if !word.starts_with("--") => {
+                    if request.action.is_empty() {
+                        request.action = word.to_string();
+                    } else if request.target.is_none() {
+                        request.target = Some(word.to_string());
+                    } else {
+                        return Err(format!("Unexpected argument: {arg}"));
+                    }
+                }
+                _ => return Err(format!("Unrecognized argument: {arg}")),
+            }
+        }
+        if let Some(CliCommand::Run(request)) = &cli.command
+            && request.action.is_empty()
+        {
+            return Err(String::from("run expects an action, e.g. `lord run mail`"));
+        }
+        if cli
+            .profile
+            .as_deref()
+            .is_some_and(|name| name.trim().is_empty())
+        {
+            return Err(String::from("--profile requires a name"));
+        }
+        Ok(cli)
+    }
+}
+
 struct Game {
-    location: Location,
+    location: String,
+    world: Vec<LocationDef>,
     config: Config,
     character: Character,
+    save: SaveData,
+    save_path: Option<PathBuf>,
+    journal: Journal,
+    events: Vec<GameEvent>,
+    /// Modification times of the config layers as of the last (re)load.
+    config_stamp: Vec<(PathBuf, Option<SystemTime>)>,
+    /// Shown above the next menu, e.g. the result of a config reload.
+    notices: Vec<(String, &'static str)>,
+    /// Whether the last action reached `complete_action` and succeeded.
+    last_action_succeeded: bool,
+}
+
+/// Cleared by `--no-color` or `NO_COLOR`; `paint` then drops every escape code.
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Set from `--config`; replaces the layered lookup in `config_paths`.
+static CONFIG_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Hub variables (`LORD_PROFILE`, ...) that commands can reference as
+/// `${NAME}`; they are also exported to every child process.
+static COMMAND_VARS: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// Set by the SIGHUP handler; the hub reloads its config at the next menu.
+static CONFIG_RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// A launched tool the hub still holds a handle on.
+struct SupervisedChild {
+    child: Child,
+    label: String,
+    started_at: u64,
+    /// Where a background child's output goes.
+    log: Option<PathBuf>,
+    ok_exit_codes: Vec<i32>,
+    /// The `action_key` of the action that launched it, once known.
+    action: Option<String>,
+}
+
+/// Every child started by `spawn_command`; `reap_children` drops the exited ones.
+static CHILDREN: Mutex<Vec<SupervisedChild>> = Mutex::new(Vec::new());
+
+/// Set by the SIGCHLD handler; the hub menu stops waiting for input so the
+/// finished tool is reaped and reported right away.
+static CHILD_EXITED: AtomicBool = AtomicBool::new(false);
+
+/// Set by `lord run`, which waits for background commands so that its exit
+/// status can reflect theirs.
+static WAIT_FOR_COMMANDS: AtomicBool = AtomicBool::new(false);
+
+/// Set by the SIGINT handler while `stream_command` runs a child.
+static STREAM_INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn interrupt_stream(_signal: libc::c_int) {
+    STREAM_INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+extern "C" fn request_config_reload(_signal: libc::c_int) {
+    CONFIG_RELOAD_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+extern "C" fn note_child_exit(_signal: libc::c_int) {
+    CHILD_EXITED.store(true, Ordering::SeqCst);
 }
 
 impl Game {
     fn new(config: Config) -> Self {
-        let character = Character::new(&config);
+        let save = SaveData::new(&determine_character_name(), &config);
+        let character = Character::new(&config, &save);
+        let (world, skipped) = assemble_world(&config);
         Self {
-            location: Location::TownSquare,
+            location: START_LOCATION.to_string(),
+            world,
             config,
             character,
+            save,
+            save_path: None,
+            journal: Journal::open(),
+            events: Vec::new(),
+            config_stamp: config_stamp(),
+            notices: skipped_entries_notice(&skipped).into_iter().collect(),
+            last_action_succeeded: false,
         }
     }
 
-    fn run(&mut self) -> io::Result<()> {
-        show_splash_screen()?;
+    fn run(&mut self, cli: &Cli) -> io::Result<()> {
+        // Without SA_RESTART, so these also wake a hub menu waiting for input.
+        install_signal_handler(libc::SIGHUP, request_config_reload);
+        install_signal_handler(libc::SIGCHLD, note_child_exit);
+        watch_config_files();
+        if !cli.no_splash {
+            show_splash_screen()?;
+        }
+        migrate_legacy_save();
+        let selected = match cli.profile.as_deref() {
+            Some(name) => Some(self.open_or_create_profile(name)),
+            None => self.pick_profile()?,
+        };
+        let Some((save, save_path)) = selected else {
+            println!("Until next time, traveler.");
+            return Ok(());
+        };
+        self.load_profile(save, save_path);
+        if let Some(start) = &cli.start {
+            self.location = start.clone();
+        }
         loop {
-            let keep_playing = match self.location {
-                Location::TownSquare => self.handle_town_square()?,
-                Location::Graveyard => self.handle_graveyard()?,
-                Location::Room => self.handle_room()?,
-            };
+            let keep_playing = self.handle_location()?;
             if !keep_playing {
+                self.persist();
                 println!("Until next time, traveler.");
                 break;
             }
@@ -110,145 +413,513 @@ impl Game {
         Ok(())
     }
 
-    fn handle_town_square(&mut self) -> io::Result<bool> {
+    fn load_profile(&mut self, save: SaveData, save_path: Option<PathBuf>) {
+        self.character = Character::new(&self.config, &save);
+        self.location = if self.world.iter().any(|def| def.id == save.last_location) {
+            save.last_location.clone()
+        } else {
+            START_LOCATION.to_string()
+        };
+        self.save = save;
+        self.save_path = save_path;
+        self.publish_command_vars();
+        self.record_daily_visit();
+        self.refresh_daily_turns();
+        self.dispatch_events();
+        self.persist();
+    }
+
+    /// Refreshes the `LORD_*` variables available to configured commands.
+    fn publish_command_vars(&self) {
+        let mut vars = COMMAND_VARS.lock().unwrap_or_else(PoisonError::into_inner);
+        vars.insert("LORD_PROFILE".into(), self.character.name.clone());
+        vars.insert("LORD_LEVEL".into(), self.character.level.to_string());
+        if let Some(dir) = data_dir() {
+            vars.insert("LORD_DATA_DIR".into(), dir.display().to_string());
+        }
+    }
+
+    fn record_daily_visit(&mut self) {
+        let today = local_timestamp(unix_timestamp())[..10].to_string();
+        if self.save.last_active_day == today {
+            return;
+        }
+        self.save.streak_days =
+            next_streak(&self.save.last_active_day, &today, self.save.streak_days);
+        self.save.last_active_day = today;
+        self.events.push(GameEvent::DailyVisit {
+            streak: self.save.streak_days,
+        });
+    }
+
+    /// Runs one action for `lord run` against the `--profile` save, or the
+    /// most recently played one, and returns the process exit code.
+    fn run_action(&mut self, cli: &Cli, request: &RunRequest) -> io::Result<i32> {
+        let selected = match cli.profile.as_deref() {
+            Some(name) => Some(self.open_or_create_profile(name)),
+            None => list_profiles()
+                .into_iter()
+                .filter_map(|profile| Some((profile.save.ok()?, profile.path)))
+                .max_by_key(|(save, _)| save.updated_at)
+                .map(|(save, path)| (save, Some(path))),
+        };
+        let Some((save, save_path)) = selected else {
+            eprintln!("No saved profile yet; pass --profile <name> to create one.");
+            return Ok(2);
+        };
+        self.load_profile(save, save_path);
+        self.last_action_succeeded = false;
+        WAIT_FOR_COMMANDS.store(true, Ordering::SeqCst);
+        let target = request.target.as_deref().map(str::trim);
+        match (request.action.as_str(), target) {
+            ("mail" | "check_mail", None) => self.perform_check_mail()?,
+            ("computer", None) => self.perform_use_computer()?,
+            ("closet", None) => self.perform_open_closet()?,
+            ("explore", None) => self.perform_explore_world()?,
+            ("rest" | "lay_down", None) => self.perform_lay_down()?,
+            ("tombs" | "search_tombs", None) => self.perform_search_tombs()?,
+            ("screensaver", None) => self.perform_screensaver()?,
+            ("chest", Some(name)) => {
+                let tools: Vec<NamedCommand> = self
+                    .config
+                    .actions
+                    .chest_tools()
+                    .iter()
+                    .filter(|tool| tool.is_valid())
+                    .cloned()
+                    .collect();
+                let Some(tool) = tools
+                    .iter()
+                    .find(|tool| tool.name.eq_ignore_ascii_case(name))
+                else {
+                    let names: Vec<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+                    eprintln!(
+                        "No chest tool named \"{name}\". Available: {}",
+                        names.join(", ")
+                    );
+                    return Ok(2);
+                };
+                self.perform_chest_tool(tool)?;
+            }
+            ("pocket", Some(name)) => {
+                let pockets = &self.character.pockets;
+                let Some(index) = pockets
+                    .iter()
+                    .position(|pocket| pocket.name.eq_ignore_ascii_case(name))
+                else {
+                    let names: Vec<&str> =
+                        pockets.iter().map(|pocket| pocket.name.as_str()).collect();
+                    eprintln!(
+                        "No pocket named \"{name}\". Available: {}",
+                        names.join(", ")
+                    );
+                    return Ok(2);
+                };
+                self.perform_use_pocket(index)?;
+            }
+            ("custom", Some(label)) => {
+                let Some(action) = self
+                    .world
+                    .iter()
+                    .flat_map(|location| &location.actions)
+                    .find(|action| {
+                        !action.spec.is_empty() && action.label.eq_ignore_ascii_case(label)
+                    })
+                    .cloned()
+                else {
+                    eprintln!("No custom action labelled \"{label}\".");
+                    return Ok(2);
+                };
+                self.perform_custom_command(&action.label, &action.spec, action.capture)?;
+            }
+            ("chest" | "pocket" | "custom", None) => {
+                eprintln!("`lord run {}` needs a name.", request.action);
+                return Ok(2);
+            }
+            (action, _) => {
+                eprintln!(
+                    "Unknown action \"{action}\". Try mail, computer, closet, explore, rest, tombs, screensaver, chest <name>, pocket <name>, or custom <label>."
+                );
+                return Ok(2);
+            }
+        }
+        self.persist();
+        Ok(if self.last_action_succeeded { 0 } else { 1 })
+    }
+
+    fn open_or_create_profile(&self, name: &str) -> (SaveData, Option<PathBuf>) {
+        let name = name.trim();
+        let Some(path) = profile_path(name) else {
+            return (SaveData::new(name, &self.config), None);
+        };
+        match SaveData::load(&path) {
+            Ok(Some(save)) => (save, Some(path)),
+            Ok(None) => (SaveData::new(name, &self.config), Some(path)),
+            Err(err) => {
+                // Never overwrite a save we could not read; the operator can
+                // inspect or move it aside before the next session.
+                eprintln!("Progress will not be saved this session: {err}");
+                (SaveData::new(name, &self.config), None)
+            }
+        }
+    }
+
+    fn pick_profile(&self) -> io::Result<Option<(SaveData, Option<PathBuf>)>> {
         loop {
+            let profiles = list_profiles();
             clear_screen();
-            show_location(Location::TownSquare, "== Town Square ==");
-            print_option("G", "Go to the graveyard");
-            print_option("R", "Return to your room");
-            print_option("X", "Examine your dossier");
+            println!();
+            print_centered_colored("== Operator Profiles ==", COLOR_TITLE);
+            for (index, profile) in profiles.iter().enumerate() {
+                let slot = (index + 1).to_string();
+                let summary = match &profile.save {
+                    Ok(save) => format!("{} — Lv {} ({} XP)", save.name, save.level, save.xp),
+                    Err(_) => format!("{} — (unreadable save)", profile.file_stem),
+                };
+                print_option(&slot, &summary);
+            }
+            print_option("N", "New operator profile");
+            if !profiles.is_empty() {
+                print_option("D", "Delete a profile");
+            }
             print_option("Q", "Quit the adventure");
-            match read_choice()? {
-                Some('g') => {
-                    self.location = Location::Graveyard;
-                    return Ok(true);
-                }
-                Some('r') => {
-                    self.location = Location::Room;
-                    return Ok(true);
+            let Some(input) = read_line_trimmed()? else {
+                return Ok(None);
+            };
+            if input.eq_ignore_ascii_case("q") {
+                return Ok(None);
+            }
+            if input.eq_ignore_ascii_case("n") {
+                if let Some(selected) = self.prompt_new_profile(&profiles)? {
+                    return Ok(Some(selected));
                 }
-                Some('x') => {
-                    self.perform_character_sheet()?;
+                continue;
+            }
+            if input.eq_ignore_ascii_case("d") && !profiles.is_empty() {
+                prompt_delete_profile(&profiles)?;
+                continue;
+            }
+            match input.parse::<usize>() {
+                Ok(choice) if choice >= 1 && choice <= profiles.len() => {
+                    let profile = &profiles[choice - 1];
+                    return Ok(Some(match &profile.save {
+                        Ok(save) => (save.clone(), Some(profile.path.clone())),
+                        Err(err) => {
+                            eprintln!("Progress will not be saved this session: {err}");
+                            wait_for_continue()?;
+                            (SaveData::new(&profile.file_stem, &self.config), None)
+                        }
+                    }));
                 }
-                Some('q') => return Ok(false),
-                None => return Ok(false),
-                _ => println!("That action is not available."),
+                _ => {}
+            }
+        }
+    }
+
+    fn prompt_new_profile(
+        &self,
+        profiles: &[ProfileEntry],
+    ) -> io::Result<Option<(SaveData, Option<PathBuf>)>> {
+        let suggested = determine_character_name();
+        print_centered_colored(
+            &format!("Operator name (ENTER for {suggested}):"),
+            COLOR_PROMPT,
+        );
+        let Some(input) = read_line_trimmed()? else {
+            return Ok(None);
+        };
+        let name = if input.is_empty() { suggested } else { input };
+        let Some(path) = profile_path(&name) else {
+            return Ok(Some((SaveData::new(&name, &self.config), None)));
+        };
+        if profiles.iter().any(|profile| profile.path == path) {
+            print_centered_colored("A profile with that name already exists.", COLOR_PROMPT);
+            wait_for_continue()?;
+            return Ok(None);
+        }
+        Ok(Some((SaveData::new(&name, &self.config), Some(path))))
+    }
+
+    fn persist(&mut self) {
+        self.sync_save();
+        let Some(path) = self.save_path.as_deref() else {
+            return;
+        };
+        if let Err(err) = self.save.write(path) {
+            eprintln!("Failed to save progress to {}: {err}", path.display());
+        }
+    }
+
+    /// Copies the live character and location into `save`.
+    fn sync_save(&mut self) {
+        self.save.level = self.character.level;
+        self.save.xp = self.character.xp;
+        self.save.stats = self.character.stats.clone();
+        self.save.clothing = Some(
+            self.character
+                .wardrobe
+                .iter()
+                .map(|item| item.name.clone())
+                .collect(),
+        );
+        self.save.equipped = Some(self.character.equipped.clone());
+        self.save.last_location = self.location.clone();
+        self.save.updated_at = unix_timestamp();
+    }
+
+    /// Reloads the config after SIGHUP or when any config layer changed on disk.
+    fn poll_config_reload(&mut self) {
+        let requested = CONFIG_RELOAD_REQUESTED.swap(false, Ordering::SeqCst);
+        if requested || config_stamp() != self.config_stamp {
+            self.reload_config();
+        }
+    }
+
+    /// Re-reads every config layer and swaps it in, noting the outcome. When a
+    /// layer fails to parse or is invalid, the running config stays; entries
+    /// that clash are left out and listed.
+    fn reload_config(&mut self) {
+        self.config_stamp = config_stamp();
+        let layers = ConfigLayers::read();
+        if !layers.errors.is_empty() {
+            let mut notice = String::from("Config reload failed; keeping the previous config.");
+            for problem in layers.errors {
+                notice.push('\n');
+                notice.push_str(&problem);
             }
+            self.notices.push((notice, COLOR_ERROR));
+            return;
+        }
+        let config = layers.config();
+        let (world, skipped) = assemble_world(&config);
+        self.sync_save();
+        self.world = world;
+        self.character = Character::new(&config, &self.save);
+        self.config = config;
+        if !self.world.iter().any(|def| def.id == self.location) {
+            self.location = START_LOCATION.to_string();
+        }
+        self.notices
+            .push((String::from("Config reloaded."), COLOR_PROMPT));
+        self.notices.extend(skipped_entries_notice(&skipped));
+    }
+
+    fn show_notice(&mut self) {
+        for (notice, color) in self.notices.drain(..) {
+            for line in notice.lines() {
+                println!("{}{line}{}", paint(color), paint(RESET));
+            }
+        }
+    }
+
+    /// Reaps finished background tools, noting the ones whose exit status
+    /// counts as a failure and updating (and saving) the record for the
+    /// action that launched each of them.
+    fn record_finished_tools(&mut self) {
+        CHILD_EXITED.store(false, Ordering::SeqCst);
+        let mut record_changed = false;
+        for (entry, status) in reap_children() {
+            let description = describe_exit(status);
+            let accepted = exit_accepted(status, &entry.ok_exit_codes);
+            if let Some(key) = &entry.action {
+                record_changed |= if accepted {
+                    self.save.failed_actions.remove(key).is_some()
+                } else {
+                    self.save
+                        .failed_actions
+                        .insert(key.clone(), description.clone())
+                        .is_none_or(|previous| previous != description)
+                };
+            }
+            if !accepted {
+                self.notices
+                    .push((format!("{} {description}.", entry.label), COLOR_ERROR));
+            }
+        }
+        if record_changed {
+            self.persist();
         }
     }
 
-    fn handle_graveyard(&mut self) -> io::Result<bool> {
+    fn handle_location(&mut self) -> io::Result<bool> {
         loop {
+            self.poll_config_reload();
+            self.record_finished_tools();
+            let Some(location) = self
+                .world
+                .iter()
+                .find(|def| def.id == self.location)
+                .cloned()
+            else {
+                // The location vanished from the config; fall back to the start.
+                self.location = START_LOCATION.to_string();
+                return Ok(true);
+            };
             clear_screen();
-            show_location(Location::Graveyard, "== Graveyard ==");
-            print_option("S", "Search for encrypted tombs");
-            print_option("T", "Trek back to the town square");
+            self.show_notice();
+            show_location(&location);
+            for action in &location.actions {
+                print_option(&action.key.to_uppercase(), &action.label);
+            }
+            for exit in &location.exits {
+                print_option(&exit.key.to_uppercase(), &exit.label);
+            }
+            // A configured `P` wins; the panel is still on every other menu.
+            let process_panel = location.hotkey_label("P").is_none();
             print_option("X", "Examine your dossier");
+            if process_panel {
+                print_option("P", "Active processes");
+            }
             print_option("Q", "Quit the adventure");
-            match read_choice()? {
-                Some('s') => {
-                    self.perform_search_tombs()?;
-                    self.reward_xp(XP_MEDIUM);
-                    wait_for_continue()?;
-                }
-                Some('t') => {
-                    self.location = Location::TownSquare;
-                    return Ok(true);
-                }
+            let choice = match read_menu_choice() {
+                // SIGHUP or SIGCHLD: the top of the loop reloads or reaps, and redraws.
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                choice => choice?,
+            };
+            match choice {
                 Some('x') => {
                     self.perform_character_sheet()?;
                 }
+                Some('p') if process_panel => {
+                    self.perform_process_panel()?;
+                }
                 Some('q') => return Ok(false),
                 None => return Ok(false),
-                _ => println!("Bones do not respond to that command."),
+                Some(choice) => {
+                    if let Some(action) = location
+                        .actions
+                        .iter()
+                        .find(|action| hotkey_matches(&action.key, choice))
+                    {
+                        self.perform_location_action(action)?;
+                    } else if let Some(exit) = location
+                        .exits
+                        .iter()
+                        .find(|exit| hotkey_matches(&exit.key, choice))
+                    {
+                        if self.world.iter().any(|def| def.id == exit.to) {
+                            self.location = exit.to.clone();
+                            return Ok(true);
+                        }
+                        println!("That path leads nowhere.");
+                    } else {
+                        println!("{}", location.unknown_message());
+                    }
+                }
             }
         }
     }
 
-    fn handle_room(&mut self) -> io::Result<bool> {
-        loop {
-            clear_screen();
-            show_location(Location::Room, "== Your Safehouse ==");
-            print_option("M", "Mail: check the courier satchel");
-            print_option("C", "Computer: boot the virtual mainframe");
-            print_option("H", "Hardware chest: deploy network tools");
-            print_option("O", "Open the neon closet (games)");
-            print_option("E", "Explore the world grid");
-            print_option("L", "Lay down for a short rest");
-            print_option("B", "Bedtime: start the screensaver");
-            print_option("T", "Town square awaits");
-            print_option("X", "Examine your dossier");
-            print_option("Q", "Quit the adventure");
-            match read_choice()? {
-                Some('m') => {
-                    self.perform_check_mail()?;
-                    self.reward_xp(XP_SMALL);
-                    wait_for_continue()?;
-                }
-                Some('c') => {
-                    self.perform_use_computer()?;
-                    self.reward_xp(XP_SMALL);
-                    wait_for_continue()?;
-                }
-                Some('h') => {
-                    self.perform_open_chest()?;
-                    self.reward_xp(XP_MEDIUM);
-                }
-                Some('o') => {
-                    self.perform_open_closet()?;
-                    self.reward_xp(XP_SMALL);
-                    wait_for_continue()?;
-                }
-                Some('e') => {
-                    self.perform_explore_world()?;
-                    self.reward_xp(XP_SMALL);
-                    wait_for_continue()?;
-                }
-                Some('l') => {
-                    self.perform_lay_down()?;
-                    self.reward_xp(XP_SMALL);
-                    wait_for_continue()?;
-                }
-                Some('b') => {
-                    self.perform_screensaver()?;
-                    self.reward_xp(XP_MEDIUM);
+    fn perform_location_action(&mut self, action: &LocationAction) -> io::Result<()> {
+        if let Some(builtin) = &action.action {
+            // The chest and screensaver draw their own screens, so they skip
+            // the "press ENTER" pause the other actions share.
+            match builtin.as_str() {
+                "search_tombs" => self.perform_search_tombs()?,
+                "check_mail" => self.perform_check_mail()?,
+                "computer" => self.perform_use_computer()?,
+                "closet" => self.perform_open_closet()?,
+                "explore" => self.perform_explore_world()?,
+                "lay_down" => self.perform_lay_down()?,
+                "chest" => return self.perform_open_chest(),
+                "screensaver" => return self.perform_screensaver(),
+                other => println!("Nothing here knows how to \"{other}\"."),
+            }
+            return wait_for_continue();
+        }
+        if action.spec.is_empty() {
+            println!("Nothing happens.");
+            return wait_for_continue();
+        }
+        self.perform_custom_command(&action.label, &action.spec, action.capture)?;
+        wait_for_continue()
+    }
+
+    fn perform_custom_command(
+        &mut self,
+        label: &str,
+        command: &CommandSpec,
+        capture: bool,
+    ) -> io::Result<()> {
+        if !self.ensure_turns("custom") {
+            return Ok(());
+        }
+        let outcome = if capture {
+            match stream_command(command, command.timeout(self.config.actions.timeout_secs())) {
+                Ok(run) => {
+                    if !run.printed {
+                        println!("The command completed without output.");
+                    }
+                    exit_outcome(command, run.status)
                 }
-                Some('t') => {
-                    self.location = Location::TownSquare;
-                    return Ok(true);
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {
+                    println!("{label} was {err}.");
+                    ActionOutcome::Cancelled
                 }
-                Some('x') => {
-                    self.perform_character_sheet()?;
+                Err(err) => {
+                    eprintln!("{label} failed: {err}");
+                    ActionOutcome::from_stream_error(&err)
                 }
-                Some('q') => return Ok(false),
-                None => return Ok(false),
-                _ => println!("The room remains silent."),
             }
-        }
+        } else {
+            launch_command(command, &format!("{label} failed to launch"))
+        };
+        self.complete_action("custom", Some(label), Some(command.argv()), outcome);
+        Ok(())
     }
 
     fn perform_search_tombs(&mut self) -> io::Result<()> {
-        if let Some(command) = self.config.actions.search_tombs_command() {
-            match run_command_and_capture(command) {
-                Ok(output) => {
-                    if output.trim().is_empty() {
+        if !self.ensure_turns("search_tombs") {
+            return Ok(());
+        }
+        let command = self.config.actions.search_tombs_command().cloned();
+        let mut found = false;
+        let outcome = if let Some(command) = &command {
+            let timeout = command.timeout(self.config.actions.timeout_secs());
+            match stream_command(command, timeout) {
+                Ok(run) => {
+                    if !run.printed {
                         println!("The command completed without output.");
-                    } else {
-                        println!("{output}");
                     }
+                    let outcome = exit_outcome(command, run.status);
+                    found = outcome.succeeded() && !run.stdout.trim().is_empty();
+                    outcome
                 }
                 Err(err) => {
-                    eprintln!("Failed to run configured search: {err}");
-                    self.perform_builtin_tomb_search()?;
+                    // Only a search that never ran falls back to the built-in one.
+                    match err.kind() {
+                        io::ErrorKind::TimedOut => eprintln!("The configured search {err}."),
+                        io::ErrorKind::Interrupted => {
+                            eprintln!("The configured search was {err}.");
+                        }
+                        _ => {
+                            eprintln!("Failed to run configured search: {err}");
+                            found = self.perform_builtin_tomb_search()?;
+                        }
+                    }
+                    ActionOutcome::from_stream_error(&err)
                 }
             }
         } else {
-            self.perform_builtin_tomb_search()?;
+            found = self.perform_builtin_tomb_search()?;
+            ActionOutcome::Builtin
+        };
+        if found {
+            let credits = 5 + self.character.strength();
+            self.character.stats.credits += credits;
+            println!("You pry {credits} credits from the crypt's offering slots.");
+            self.events.push(GameEvent::TombFound);
         }
+        self.complete_action(
+            "search_tombs",
+            None,
+            command.as_ref().map(CommandSpec::argv),
+            outcome,
+        );
         Ok(())
     }
 
-    fn perform_builtin_tomb_search(&self) -> io::Result<()> {
+    fn perform_builtin_tomb_search(&self) -> io::Result<bool> {
         println!("You sift through dusty ledgers, looking for .tomb vaults...\n");
         let mut any_found = false;
         for dir in tomb_search_paths() {
@@ -256,11 +927,9 @@ impl Game {
                 let mut matches = Vec::new();
                 for entry in entries.flatten() {
                     let path = entry.path();
-                    if let Some(ext) = path.extension() {
-                        if ext == "tomb" {
-                            matches.push(path);
-                            continue;
-                        }
+                    if path.extension().is_some_and(|ext| ext == "tomb") {
+                        matches.push(path);
+                        continue;
                     }
                     if path.is_dir() {
                         matches.push(path);
@@ -281,64 +950,106 @@ impl Game {
                 "No tombs were discovered. Configure a search command in lord_config.toml if you rely on the tomb CLI."
             );
         }
-        Ok(())
+        Ok(any_found)
     }
 
     fn perform_check_mail(&mut self) -> io::Result<()> {
-        if let Some(command) = self.config.actions.check_mail_command() {
-            if let Err(err) = spawn_command(command) {
-                eprintln!("Unable to launch mail command: {err}");
-            }
+        if !self.ensure_turns("check_mail") {
+            return Ok(());
+        }
+        let command = self.config.actions.check_mail_command().cloned();
+        let outcome = if let Some(command) = &command {
+            launch_command(command, "Unable to launch mail command")
         } else {
             println!(
                 "No mail command configured. Add one under [actions] -> check_mail in lord_config.toml."
             );
-        }
+            ActionOutcome::Unconfigured
+        };
+        self.complete_action(
+            "check_mail",
+            None,
+            command.as_ref().map(CommandSpec::argv),
+            outcome,
+        );
         Ok(())
     }
 
     fn perform_lay_down(&mut self) -> io::Result<()> {
-        if let Some(command) = self.config.actions.lay_down_command() {
-            if let Err(err) = spawn_command(command) {
-                eprintln!("Unable to start short rest command: {err}");
-            }
+        if !self.ensure_turns("lay_down") {
+            return Ok(());
+        }
+        let command = self.config.actions.lay_down_command().cloned();
+        let outcome = if let Some(command) = &command {
+            launch_command(command, "Unable to start short rest command")
         } else {
             println!("You stretch out on the cot. A moment of calm washes over you.");
+            ActionOutcome::Builtin
+        };
+        if outcome.succeeded() {
+            let max_hp = self.character.max_hp();
+            self.character.stats.hp = max_hp;
+            println!("Your wounds knit back together. (HP {max_hp}/{max_hp})");
         }
+        self.complete_action(
+            "lay_down",
+            None,
+            command.as_ref().map(CommandSpec::argv),
+            outcome,
+        );
         Ok(())
     }
 
     fn perform_screensaver(&mut self) -> io::Result<()> {
-        if let Some(command) = self.config.actions.activate_screensaver_command() {
-            if let Err(err) = spawn_command(command) {
-                eprintln!("Unable to start screensaver command: {err}");
-            }
+        if !self.ensure_turns("screensaver") {
+            return Ok(());
+        }
+        let command = self.config.actions.activate_screensaver_command().cloned();
+        let outcome = if let Some(command) = &command {
+            launch_command(command, "Unable to start screensaver command")
         } else {
             run_builtin_screensaver()?;
-        }
+            ActionOutcome::Builtin
+        };
+        self.complete_action(
+            "screensaver",
+            None,
+            command.as_ref().map(CommandSpec::argv),
+            outcome,
+        );
         Ok(())
     }
 
     fn perform_use_computer(&mut self) -> io::Result<()> {
-        if let Some(command) = self.config.actions.computer_terminal_command() {
-            if let Err(err) = spawn_command(command) {
-                eprintln!("The cyberdeck refuses to boot: {err}");
-            }
+        if !self.ensure_turns("computer") {
+            return Ok(());
+        }
+        let command = self.config.actions.computer_terminal_command().cloned();
+        let outcome = if let Some(command) = &command {
+            launch_command(command, "The cyberdeck refuses to boot")
         } else {
             println!(
                 "No computer command configured. Assign actions.computer_terminal to launch VMware, virt-manager, etc."
             );
-        }
+            ActionOutcome::Unconfigured
+        };
+        self.complete_action(
+            "computer",
+            None,
+            command.as_ref().map(CommandSpec::argv),
+            outcome,
+        );
         Ok(())
     }
 
     fn perform_open_chest(&mut self) -> io::Result<()> {
-        let tools: Vec<&NamedCommand> = self
+        let tools: Vec<NamedCommand> = self
             .config
             .actions
             .chest_tools()
             .iter()
             .filter(|tool| tool.is_valid())
+            .cloned()
             .collect();
         if tools.is_empty() {
             println!(
@@ -347,12 +1058,25 @@ impl Game {
             return Ok(());
         }
         loop {
+            self.record_finished_tools();
             clear_screen();
             println!();
             print_centered_colored("== Tech Chest ==", COLOR_TITLE);
             for (index, tool) in tools.iter().enumerate() {
                 let slot = (index + 1).to_string();
-                print_option(&slot, &tool.name);
+                let key = action_key("chest", Some(&tool.name));
+                match self.save.failed_actions.get(&key) {
+                    Some(failure) => print_option(
+                        &slot,
+                        &format!(
+                            "{} {}(failed last time: {failure}){}",
+                            tool.name,
+                            paint(COLOR_ERROR),
+                            paint(RESET)
+                        ),
+                    ),
+                    None => print_option(&slot, &tool.name),
+                }
             }
             print_option("Q", "Return to the room");
             match read_line_trimmed()? {
@@ -366,13 +1090,8 @@ impl Game {
                     }
                     match input.parse::<usize>() {
                         Ok(choice) if choice >= 1 && choice <= tools.len() => {
-                            if let Some(tool) = tools.get(choice - 1) {
-                                if let Some(command) = tool.command() {
-                                    if let Err(err) = spawn_command(command) {
-                                        eprintln!("Failed to launch {}: {err}", tool.name);
-                                    }
-                                }
-                            }
+                            self.perform_chest_tool(&tools[choice - 1])?;
+                            wait_for_continue()?;
                         }
                         _ => println!("The chest stays locked unless you choose a valid slot."),
                     }
@@ -382,411 +1101,4182 @@ impl Game {
         Ok(())
     }
 
+    fn perform_chest_tool(&mut self, tool: &NamedCommand) -> io::Result<()> {
+        if !self.ensure_turns("chest") {
+            return Ok(());
+        }
+        let command = &tool.spec;
+        if !command.is_empty() {
+            let outcome = launch_command(command, &format!("Failed to launch {}", tool.name));
+            self.complete_action("chest", Some(&tool.name), Some(command.argv()), outcome);
+        }
+        Ok(())
+    }
+
     fn perform_open_closet(&mut self) -> io::Result<()> {
-        if let Some(command) = self.config.actions.closet_launcher_command() {
-            if let Err(err) = spawn_command(command) {
-                eprintln!("Unable to open the neon closet: {err}");
-            }
+        if !self.ensure_turns("closet") {
+            return Ok(());
+        }
+        let command = self.config.actions.closet_launcher_command().cloned();
+        let outcome = if let Some(command) = &command {
+            launch_command(command, "Unable to open the neon closet")
         } else {
             println!(
                 "No closet launcher configured. Set actions.closet_launcher to your preferred game hub."
             );
-        }
+            ActionOutcome::Unconfigured
+        };
+        self.complete_action(
+            "closet",
+            None,
+            command.as_ref().map(CommandSpec::argv),
+            outcome,
+        );
         Ok(())
     }
 
     fn perform_explore_world(&mut self) -> io::Result<()> {
-        if let Some(command) = self.config.actions.explore_world_command() {
-            if let Err(err) = spawn_command(command) {
-                eprintln!("Exploration systems failed to boot: {err}");
-            }
+        if !self.ensure_turns("explore") {
+            return Ok(());
+        }
+        let command = self.config.actions.explore_world_command().cloned();
+        let outcome = if let Some(command) = &command {
+            launch_command(command, "Exploration systems failed to boot")
         } else {
             println!(
                 "No exploration route configured. Point actions.explore_world at a browser like Firefox."
             );
+            ActionOutcome::Unconfigured
+        };
+        self.complete_action(
+            "explore",
+            None,
+            command.as_ref().map(CommandSpec::argv),
+            outcome,
+        );
+        Ok(())
+    }
+
+    /// Lists the tools the hub launched that are still running and lets the
+    /// operator send them SIGTERM or SIGKILL.
+    fn perform_process_panel(&mut self) -> io::Result<()> {
+        let mut status: Option<(String, &'static str)> = None;
+        loop {
+            self.record_finished_tools();
+            let running: Vec<(u32, String, u64, Option<PathBuf>)> = CHILDREN
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .map(|entry| {
+                    (
+                        entry.child.id(),
+                        entry.label.clone(),
+                        entry.started_at,
+                        entry.log.clone(),
+                    )
+                })
+                .collect();
+            clear_screen();
+            println!();
+            print_centered_colored("== Active Processes ==", COLOR_TITLE);
+            if let Some((message, color)) = status.take() {
+                println!("{}{message}{}", paint(color), paint(RESET));
+            }
+            if running.is_empty() {
+                println!("No launched tools are running.");
+            }
+            let now = unix_timestamp();
+            for (index, (pid, label, started_at, log)) in running.iter().enumerate() {
+                let started = local_timestamp(*started_at);
+                print_option(
+                    &(index + 1).to_string(),
+                    &format!(
+                        "PID {pid} {label} (since {}, {})",
+                        &started[11..19],
+                        format_elapsed(now.saturating_sub(*started_at))
+                    ),
+                );
+                if let Some(log) = log {
+                    println!("      log: {}", log.display());
+                }
+            }
+            print_option("R", "Refresh");
+            print_option("Q", "Back");
+            let choice = match read_line_trimmed()? {
+                None => break,
+                Some(input) if input.eq_ignore_ascii_case("q") => break,
+                Some(input) if input.is_empty() || input.eq_ignore_ascii_case("r") => continue,
+                Some(input) => input,
+            };
+            let Some((pid, label, _, _)) = choice
+                .parse::<usize>()
+                .ok()
+                .and_then(|slot| running.get(slot.checked_sub(1)?))
+            else {
+                status = Some((String::from("Choose a listed process."), COLOR_ERROR));
+                continue;
+            };
+            print_option("T", &format!("Terminate PID {pid} (SIGTERM)"));
+            print_option("K", &format!("Kill PID {pid} (SIGKILL)"));
+            print_option("Q", "Leave it running");
+            let (signal, name) = match read_choice()? {
+                Some('t') => (libc::SIGTERM, "SIGTERM"),
+                Some('k') => (libc::SIGKILL, "SIGKILL"),
+                _ => continue,
+            };
+            status = Some(match signal_child(*pid, signal) {
+                Ok(()) => (format!("Sent {name} to {label} (PID {pid})."), COLOR_PROMPT),
+                Err(err) => (
+                    format!("Could not signal {label} (PID {pid}): {err}"),
+                    COLOR_ERROR,
+                ),
+            });
+            // Give the signal a moment to land so the list reflects it.
+            thread::sleep(Duration::from_millis(200));
         }
         Ok(())
     }
 
     fn perform_character_sheet(&mut self) -> io::Result<()> {
         loop {
+            self.record_finished_tools();
             clear_screen();
             self.character.render_sheet();
+            self.render_achievements();
             if self.character.pockets.is_empty() {
-                print_centered_colored("Press ENTER to return.", COLOR_PROMPT);
-                io::stdout().flush()?;
-                let mut buffer = String::new();
-                let _ = io::stdin().read_line(&mut buffer)?;
-                break;
+                print_centered_colored(
+                    "W wardrobe, L ledger, R reload config, ENTER to return.",
+                    COLOR_PROMPT,
+                );
             } else {
                 print_centered_colored(
-                    "Select a pocket number or press ENTER to return.",
+                    "Pocket #, W wardrobe, L ledger, R reload, ENTER to return.",
                     COLOR_PROMPT,
                 );
-                match read_line_trimmed()? {
-                    None => break,
-                    Some(input) if input.is_empty() || input.eq_ignore_ascii_case("q") => break,
-                    Some(input) => match input.parse::<usize>() {
-                        Ok(choice) if choice >= 1 && choice <= self.character.pockets.len() => {
-                            if let Some(pocket) = self.character.pockets.get(choice - 1) {
-                                pocket.use_item()?;
-                                self.reward_xp(XP_SMALL);
-                                wait_for_continue()?;
-                            }
-                        }
-                        _ => print_centered_colored(
-                            "That pocket is empty or inaccessible.",
-                            COLOR_PROMPT,
-                        ),
-                    },
+            }
+            match read_line_trimmed()? {
+                None => break,
+                Some(input) if input.is_empty() || input.eq_ignore_ascii_case("q") => break,
+                Some(input) if input.eq_ignore_ascii_case("l") => self.show_ledger()?,
+                Some(input) if input.eq_ignore_ascii_case("w") => self.perform_wardrobe()?,
+                Some(input) if input.eq_ignore_ascii_case("r") => {
+                    self.reload_config();
+                    self.show_notice();
+                    wait_for_continue()?;
                 }
+                Some(input) => match input.parse::<usize>() {
+                    Ok(choice) if choice >= 1 && choice <= self.character.pockets.len() => {
+                        self.perform_use_pocket(choice - 1)?;
+                        wait_for_continue()?;
+                    }
+                    _ => print_centered_colored(
+                        "That pocket is empty or inaccessible.",
+                        COLOR_PROMPT,
+                    ),
+                },
             }
         }
         Ok(())
     }
 
+    fn perform_use_pocket(&mut self, index: usize) -> io::Result<()> {
+        if !self.ensure_turns("pocket") {
+            return Ok(());
+        }
+        let Some(pocket) = self.character.pockets.get(index) else {
+            return Ok(());
+        };
+        let name = pocket.name.clone();
+        let command = pocket.command.clone();
+        let outcome = pocket.use_item(self.config.actions.timeout_secs());
+        self.complete_action(
+            "pocket",
+            Some(&name),
+            command.as_ref().map(CommandSpec::argv),
+            outcome,
+        );
+        Ok(())
+    }
+
+    fn render_achievements(&self) {
+        let catalog = achievement_catalog(&self.config);
+        let unlocked: Vec<&AchievementDef> = catalog
+            .iter()
+            .filter(|def| self.save.achievements.contains_key(&def.id))
+            .collect();
+        println!(
+            "{}Achievements:{} {}/{}",
+            paint(COLOR_OPTION_TEXT),
+            paint(RESET),
+            unlocked.len(),
+            catalog.len()
+        );
+        if unlocked.is_empty() {
+            println!("  (none yet)");
+        }
+        for def in unlocked {
+            println!("  ★ {} — {}", def.title, def.description);
+        }
+    }
+
+    fn show_ledger(&self) -> io::Result<()> {
+        clear_screen();
+        println!("\n{}== Ledger =={}", paint(COLOR_TITLE), paint(RESET));
+        let entries = self.journal.read_entries();
+        let recent: Vec<&JournalEntry> = entries
+            .iter()
+            .filter(|entry| entry.profile == self.character.name)
+            .collect();
+        if recent.is_empty() {
+            println!("  (no recorded activity yet)");
+        } else {
+            for entry in &recent[recent.len().saturating_sub(LEDGER_ROWS)..] {
+                println!("  {}", entry.summary_line());
+            }
+        }
+        wait_for_continue()
+    }
+
+    /// Records the result of an in-game action: grants XP for successful
+    /// actions and appends the entry to the activity journal.
+    fn complete_action(
+        &mut self,
+        action: &str,
+        target: Option<&str>,
+        command: Option<&[String]>,
+        outcome: ActionOutcome,
+    ) {
+        if !matches!(outcome, ActionOutcome::Unconfigured) {
+            let cost = self.config.turns.cost_for(action);
+            let stats = &mut self.character.stats;
+            stats.turns_left = stats.turns_left.saturating_sub(cost);
+        }
+        if matches!(
+            outcome,
+            ActionOutcome::Failed(_) | ActionOutcome::TimedOut(_)
+        ) {
+            let damage = FAILURE_DAMAGE
+                .saturating_sub(self.character.defense())
+                .max(1);
+            let stats = &mut self.character.stats;
+            stats.hp = stats.hp.saturating_sub(damage);
+            println!(
+                "The backlash burns you for {damage} HP. (HP {}/{})",
+                self.character.stats.hp,
+                self.character.max_hp()
+            );
+        }
+        let key = action_key(action, target);
+        match &outcome {
+            ActionOutcome::Spawned(pid) => tag_child(*pid, key),
+            ActionOutcome::Failed(err) | ActionOutcome::TimedOut(err) => {
+                self.save.failed_actions.insert(key, err.clone());
+            }
+            ActionOutcome::Completed | ActionOutcome::Builtin => {
+                self.save.failed_actions.remove(&key);
+            }
+            // A cancel says nothing about whether the command works.
+            ActionOutcome::Unconfigured | ActionOutcome::Cancelled => {}
+        }
+        let mut xp = 0;
+        self.last_action_succeeded = outcome.succeeded();
+        if outcome.succeeded() {
+            xp = self.claim_action_xp(action, target);
+            self.events.push(GameEvent::Action {
+                action: action.to_string(),
+                target: target.map(str::to_string),
+            });
+        }
+        self.reward_xp(xp);
+        if self.dispatch_events() {
+            self.persist();
+        }
+        let entry = JournalEntry {
+            timestamp: local_timestamp(unix_timestamp()),
+            profile: self.character.name.clone(),
+            action: action.to_string(),
+            target: target.map(str::to_string),
+            location: self.location.clone(),
+            command: command.map(<[String]>::to_vec),
+            outcome: outcome.label().to_string(),
+            error: outcome.error().map(str::to_string),
+            xp,
+        };
+        self.journal.append(&entry);
+    }
+
+    /// Refreshes the daily budget and checks that `action` can be afforded.
+    /// Prints why not when the operator is out of turns or flatlined.
+    fn ensure_turns(&mut self, action: &str) -> bool {
+        self.refresh_daily_turns();
+        let stats = &self.character.stats;
+        if stats.hp == 0 && action != "lay_down" {
+            println!("You're flatlined. Lay down and recover before doing anything else.");
+            return false;
+        }
+        let cost = self.config.turns.cost_for(action);
+        if stats.turns_left < cost {
+            println!(
+                "You're out of turns for today ({} left, {} needed). Come back after midnight.",
+                stats.turns_left, cost
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Restores turns and HP once per local calendar day, like LoRD's daily reset.
+    fn refresh_daily_turns(&mut self) {
+        let today = &local_timestamp(unix_timestamp())[..10];
+        if self.character.stats.turns_day == today {
+            return;
+        }
+        let max_hp = self.character.max_hp();
+        let stats = &mut self.character.stats;
+        stats.turns_day = today.to_string();
+        stats.turns_left = self.config.turns.per_day;
+        stats.hp = max_hp;
+        println!("A new day dawns over the Neon Agora. Your turns have been restored.");
+    }
+
+    /// Looks up the configured reward for `action` and starts its cooldown.
+    /// Returns 0 while the action is still cooling down.
+    fn claim_action_xp(&mut self, action: &str, target: Option<&str>) -> u32 {
+        let amount = self.config.xp.reward_for(action, target);
+        if amount == 0 || self.character.experience_to_next_level().is_none() {
+            return 0;
+        }
+        let key = action_key(action, target);
+        let cooldown = self.config.xp.cooldown_for(action);
+        let now = unix_timestamp();
+        if let Some(last) = self.save.xp_cooldowns.get(&key) {
+            let ready_at = last.saturating_add(cooldown);
+            if now < ready_at {
+                println!(
+                    "{}No XP this time — try again in {}s.{}",
+                    paint(COLOR_PROMPT),
+                    ready_at - now,
+                    paint(RESET)
+                );
+                return 0;
+            }
+        }
+        self.save.xp_cooldowns.insert(key, now);
+        (f64::from(amount) * self.character.xp_multiplier()).round() as u32
+    }
+
     fn reward_xp(&mut self, amount: u32) {
         if amount == 0 {
             return;
         }
+        let previous_title = self.character.title().to_string();
         if let Some(new_level) = self.character.gain_xp(amount) {
+            self.publish_command_vars();
             println!(
                 "{}You feel your skills sharpen. Level up! (Lv {}){}",
-                COLOR_TITLE, new_level, RESET
+                paint(COLOR_TITLE),
+                new_level,
+                paint(RESET)
             );
-        } else {
+            println!(
+                "{}HP {}  STR {}  DEF {}{}",
+                paint(COLOR_TITLE),
+                self.character.max_hp(),
+                self.character.strength(),
+                self.character.defense(),
+                paint(RESET)
+            );
+            if self.character.title() != previous_title {
+                println!(
+                    "{}New rank: {}{}",
+                    paint(COLOR_TITLE),
+                    self.character.title(),
+                    paint(RESET)
+                );
+            }
+            self.events.push(GameEvent::LevelReached(new_level));
+        } else if let Some(threshold) = self.character.experience_to_next_level() {
             println!(
                 "{}You gain {} XP. ({}/{}){}",
-                COLOR_PROMPT,
+                paint(COLOR_PROMPT),
                 amount,
                 self.character.xp,
-                self.character.experience_to_next_level(),
-                RESET
+                threshold,
+                paint(RESET)
+            );
+        } else {
+            println!(
+                "{}You are already at the level cap. No XP gained.{}",
+                paint(COLOR_PROMPT),
+                paint(RESET)
             );
         }
+        self.dispatch_events();
+        self.persist();
+    }
+
+    /// Drains queued events, updating achievement progress and announcing
+    /// unlocks. Returns whether any event was processed.
+    fn dispatch_events(&mut self) -> bool {
+        if self.events.is_empty() {
+            return false;
+        }
+        let catalog = achievement_catalog(&self.config);
+        let chest_tools: Vec<String> = self
+            .config
+            .actions
+            .chest_tools()
+            .iter()
+            .filter(|tool| tool.is_valid())
+            .map(|tool| tool.name.clone())
+            .collect();
+        for event in std::mem::take(&mut self.events) {
+            if let GameEvent::Action { action, target } = &event {
+                *self.save.action_counts.entry(action.clone()).or_insert(0) += 1;
+                if let Some(target) = target {
+                    *self
+                        .save
+                        .action_counts
+                        .entry(format!("{action}:{target}"))
+                        .or_insert(0) += 1;
+                }
+            }
+            for def in &catalog {
+                if self.save.achievements.contains_key(&def.id)
+                    || !def.trigger.is_met(&event, &self.save, &chest_tools)
+                {
+                    continue;
+                }
+                self.save
+                    .achievements
+                    .insert(def.id.clone(), unix_timestamp());
+                println!(
+                    "{}Achievement unlocked: {}! — {}{}",
+                    paint(COLOR_TITLE),
+                    def.title,
+                    def.description,
+                    paint(RESET)
+                );
+                if let Some(item) = &def.reward_item {
+                    self.grant_item(item);
+                }
+            }
+        }
+        true
+    }
+}
+
+impl Game {
+    fn grant_item(&mut self, name: &str) {
+        if self.character.owns(name) {
+            return;
+        }
+        let item = wardrobe_item(&self.config, name);
+        println!(
+            "{}New gear: {} ({}) — added to your wardrobe.{}",
+            paint(COLOR_TITLE),
+            item.name,
+            item.rarity.label(),
+            paint(RESET)
+        );
+        self.character.wardrobe.push(item);
+    }
+
+    fn perform_wardrobe(&mut self) -> io::Result<()> {
+        loop {
+            clear_screen();
+            println!();
+            print_centered_colored("== Wardrobe ==", COLOR_TITLE);
+            for (index, item) in self.character.wardrobe.iter().enumerate() {
+                let slot = (index + 1).to_string();
+                let worn = if self.character.is_equipped(&item.name) {
+                    " [worn]"
+                } else {
+                    ""
+                };
+                print_option(&slot, &format!("{}{worn}", item.summary()));
+                if !item.description.is_empty() {
+                    println!("      {}", item.description);
+                }
+            }
+            print_option("Q", "Back to the dossier");
+            match read_line_trimmed()? {
+                None => break,
+                Some(input) if input.is_empty() || input.eq_ignore_ascii_case("q") => break,
+                Some(input) => match input.parse::<usize>() {
+                    Ok(choice) if choice >= 1 && choice <= self.character.wardrobe.len() => {
+                        self.character.toggle_equipped(choice - 1);
+                        self.persist();
+                    }
+                    _ => println!("That garment isn't in your wardrobe."),
+                },
+            }
+        }
+        Ok(())
     }
 }
 
-#[derive(Clone, Copy)]
-enum Location {
-    TownSquare,
-    Graveyard,
-    Room,
+/// Something noteworthy that happened in the hub, fed to the achievement tracker.
+enum GameEvent {
+    Action {
+        action: String,
+        target: Option<String>,
+    },
+    TombFound,
+    LevelReached(u32),
+    DailyVisit {
+        streak: u32,
+    },
 }
 
-#[derive(Default, Deserialize)]
-struct Config {
+#[derive(Clone, Deserialize)]
+struct AchievementDef {
+    id: String,
+    title: String,
     #[serde(default)]
-    actions: ActionsConfig,
+    description: String,
+    /// Wardrobe item dropped into the operator's wardrobe on unlock.
     #[serde(default)]
-    character: CharacterConfig,
+    reward_item: Option<String>,
+    #[serde(flatten)]
+    trigger: AchievementTrigger,
 }
 
-impl Config {
-    fn load() -> Self {
-        for path in config_paths() {
-            if let Ok(contents) = fs::read_to_string(&path) {
-                match toml::from_str::<Config>(&contents) {
-                    Ok(config) => return config,
-                    Err(err) => eprintln!("Failed to parse {}: {err}", path.display()),
+#[derive(Clone, Deserialize)]
+#[serde(tag = "trigger", rename_all = "snake_case")]
+enum AchievementTrigger {
+    /// Perform `action` (optionally on a specific chest tool or pocket) `count` times.
+    Action {
+        action: String,
+        #[serde(default)]
+        target: Option<String>,
+        #[serde(default = "default_achievement_count")]
+        count: u32,
+    },
+    TombFound,
+    /// Launch every configured chest tool at least once.
+    AllChestTools,
+    Level {
+        level: u32,
+    },
+    Streak {
+        days: u32,
+    },
+}
+
+/// The keys every `[[achievements]]` entry may have besides its trigger's.
+const ACHIEVEMENT_KEYS: &[&str] = &["id", "title", "description", "reward_item", "trigger"];
+
+impl AchievementTrigger {
+    /// The keys `trigger` reads from its entry, for `check-config`.
+    fn keys(trigger: &str) -> &'static [&'static str] {
+        match trigger {
+            "action" => &["action", "target", "count"],
+            "level" => &["level"],
+            "streak" => &["days"],
+            _ => &[],
+        }
+    }
+}
+
+fn default_achievement_count() -> u32 {
+    1
+}
+
+impl AchievementTrigger {
+    fn is_met(&self, event: &GameEvent, save: &SaveData, chest_tools: &[String]) -> bool {
+        match (self, event) {
+            (
+                AchievementTrigger::Action {
+                    action,
+                    target,
+                    count,
+                },
+                GameEvent::Action { action: done, .. },
+            ) if action == done => {
+                let key = match target {
+                    Some(target) => format!("{action}:{target}"),
+                    None => action.clone(),
+                };
+                save.action_counts.get(&key).copied().unwrap_or(0) >= *count
+            }
+            (AchievementTrigger::TombFound, GameEvent::TombFound) => true,
+            (AchievementTrigger::AllChestTools, GameEvent::Action { action, .. })
+                if action == "chest" =>
+            {
+                !chest_tools.is_empty()
+                    && chest_tools
+                        .iter()
+                        .all(|tool| save.action_counts.contains_key(&format!("chest:{tool}")))
+            }
+            (AchievementTrigger::Level { level }, GameEvent::LevelReached(reached)) => {
+                reached >= level
+            }
+            (AchievementTrigger::Streak { days }, GameEvent::DailyVisit { streak }) => {
+                streak >= days
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Built-in achievements followed by `[[achievements]]` from the config; a
+/// config entry with a built-in id replaces it.
+fn achievement_catalog(config: &Config) -> Vec<AchievementDef> {
+    let builtin = [
+        (
+            "first_tomb",
+            "Grave Robber",
+            "Unearthed your first tomb vault",
+            "Crypt-dust Cloak",
+            AchievementTrigger::TombFound,
+        ),
+        (
+            "full_arsenal",
+            "Full Arsenal",
+            "Deployed every tool in the tech chest",
+            "Packet Sniffer Pendant",
+            AchievementTrigger::AllChestTools,
+        ),
+        (
+            "level_10",
+            "Double Digits",
+            "Reached level 10",
+            "Overclocked Visor",
+            AchievementTrigger::Level { level: 10 },
+        ),
+        (
+            "streak_7",
+            "Creature of Habit",
+            "Visited the hub seven days in a row",
+            "Streak Runner Boots",
+            AchievementTrigger::Streak { days: 7 },
+        ),
+    ];
+    let mut catalog: Vec<AchievementDef> = builtin
+        .into_iter()
+        .map(
+            |(id, title, description, reward_item, trigger)| AchievementDef {
+                id: id.to_string(),
+                title: title.to_string(),
+                description: description.to_string(),
+                reward_item: Some(reward_item.to_string()),
+                trigger,
+            },
+        )
+        .collect();
+    for def in &config.achievements {
+        match catalog.iter_mut().find(|existing| existing.id == def.id) {
+            Some(existing) => *existing = def.clone(),
+            None => catalog.push(def.clone()),
+        }
+    }
+    catalog
+}
+
+/// A place in the hub: its banner, the actions offered there, and the exits
+/// to other locations. Built-ins come from `builtin_locations`; `[[locations]]`
+/// in the config adds new ones or extends those with a matching `id`.
+#[derive(Clone, Deserialize)]
+struct LocationDef {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    art: String,
+    #[serde(default)]
+    actions: Vec<LocationAction>,
+    #[serde(default)]
+    exits: Vec<LocationExit>,
+    /// Printed when the operator presses a key the location doesn't know.
+    #[serde(default)]
+    unknown: Option<String>,
+}
+
+#[derive(Clone, Deserialize)]
+struct LocationAction {
+    key: String,
+    label: String,
+    /// A built-in action such as `check_mail` or `chest`.
+    #[serde(default)]
+    action: Option<String>,
+    #[serde(default)]
+    capture: bool,
+    /// A command to run instead of a built-in action.
+    #[serde(flatten)]
+    spec: CommandSpec,
+}
+
+#[derive(Clone, Deserialize)]
+struct LocationExit {
+    key: String,
+    label: String,
+    to: String,
+}
+
+impl LocationDef {
+    fn builtin(
+        id: &str,
+        title: &str,
+        art: &str,
+        actions: &[(&str, &str, &str)],
+        exits: &[(&str, &str, &str)],
+        unknown: &str,
+    ) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            art: art.to_string(),
+            actions: actions
+                .iter()
+                .map(|(key, label, action)| LocationAction {
+                    key: key.to_string(),
+                    label: label.to_string(),
+                    action: Some(action.to_string()),
+                    capture: false,
+                    spec: CommandSpec::default(),
+                })
+                .collect(),
+            exits: exits
+                .iter()
+                .map(|(key, label, to)| LocationExit {
+                    key: key.to_string(),
+                    label: label.to_string(),
+                    to: to.to_string(),
+                })
+                .collect(),
+            unknown: Some(unknown.to_string()),
+        }
+    }
+
+    /// Layers a config entry over this location: a non-empty title or art
+    /// replaces the current one, and actions/exits replace those sharing a
+    /// hotkey or are appended.
+    fn merge(&mut self, other: LocationDef) {
+        if !other.title.is_empty() {
+            self.title = other.title;
+        }
+        if !other.art.is_empty() {
+            self.art = other.art;
+        }
+        if other.unknown.is_some() {
+            self.unknown = other.unknown;
+        }
+        for action in other.actions {
+            match self
+                .actions
+                .iter_mut()
+                .find(|existing| existing.key.eq_ignore_ascii_case(&action.key))
+            {
+                Some(existing) => *existing = action,
+                None => self.actions.push(action),
+            }
+        }
+        for exit in other.exits {
+            match self
+                .exits
+                .iter_mut()
+                .find(|existing| existing.key.eq_ignore_ascii_case(&exit.key))
+            {
+                Some(existing) => *existing = exit,
+                None => self.exits.push(exit),
+            }
+        }
+    }
+
+    /// The label of the action or exit already bound to `key`, if any.
+    fn hotkey_label(&self, key: &str) -> Option<&str> {
+        let key = key.trim();
+        self.actions
+            .iter()
+            .map(|action| (&action.key, &action.label))
+            .chain(self.exits.iter().map(|exit| (&exit.key, &exit.label)))
+            .find(|(existing, _)| existing.trim().eq_ignore_ascii_case(key))
+            .map(|(_, label)| label.as_str())
+    }
+
+    fn unknown_message(&self) -> &str {
+        self.unknown
+            .as_deref()
+            .unwrap_or("That action is not available.")
+    }
+}
+
+fn builtin_locations() -> Vec<LocationDef> {
+    vec![
+        LocationDef::builtin(
+            "town_square",
+            "Town Square",
+            TOWN_SQUARE_ART,
+            &[],
+            &[
+                ("G", "Go to the graveyard", "graveyard"),
+                ("R", "Return to your room", "room"),
+            ],
+            "That action is not available.",
+        ),
+        LocationDef::builtin(
+            "graveyard",
+            "Graveyard",
+            GRAVEYARD_ART,
+            &[("S", "Search for encrypted tombs", "search_tombs")],
+            &[("T", "Trek back to the town square", "town_square")],
+            "Bones do not respond to that command.",
+        ),
+        LocationDef::builtin(
+            "room",
+            "Your Safehouse",
+            ROOM_ART,
+            &[
+                ("M", "Mail: check the courier satchel", "check_mail"),
+                ("C", "Computer: boot the virtual mainframe", "computer"),
+                ("H", "Hardware chest: deploy network tools", "chest"),
+                ("O", "Open the neon closet (games)", "closet"),
+                ("E", "Explore the world grid", "explore"),
+                ("L", "Lay down for a short rest", "lay_down"),
+                ("B", "Bedtime: start the screensaver", "screensaver"),
+            ],
+            &[("T", "Town square awaits", "town_square")],
+            "The room remains silent.",
+        ),
+    ]
+}
+
+/// The hub notice listing what `assemble_world` reported, if anything.
+fn skipped_entries_notice(skipped: &[ConfigIssue]) -> Option<(String, &'static str)> {
+    if skipped.is_empty() {
+        return None;
+    }
+    let mut notice = String::from("Config problems (clashing entries are left off the menus):");
+    for issue in skipped {
+        notice.push_str("\n  - ");
+        notice.push_str(&issue.message);
+    }
+    Some((notice, COLOR_ERROR))
+}
+
+/// Builds the map and reports hotkey clashes: the global hotkeys are reserved
+/// everywhere, and an `[[actions.custom]]` entry may not reuse a key already
+/// on its location's menu. Clashing entries are left off the map; the rest
+/// of the config still applies. An entry on `P` is kept and only warned
+/// about, since the process panel yields to it.
+fn assemble_world(config: &Config) -> (Vec<LocationDef>, Vec<ConfigIssue>) {
+    let mut world = builtin_locations();
+    let mut conflicts = Vec::new();
+    for (index, def) in config.locations.iter().enumerate() {
+        let mut def = def.clone();
+        let mut keep = |list: &str, position: usize, key: &str, label: &str| {
+            let path = format!("locations.{index}.{list}.{position}.key");
+            if let Some(reserved) = reserved_hotkey(key) {
+                conflicts.push(ConfigIssue::new(
+                    &path,
+                    format!(
+                        "\"{label}\" in {} uses {reserved}, which is reserved as a global hotkey",
+                        def.id
+                    ),
+                ));
+                return false;
+            }
+            if takes_process_panel_key(key) {
+                conflicts.push(process_panel_issue(&path, label, &def.id));
+            }
+            true
+        };
+        let mut position = 0..;
+        def.actions.retain(|action| {
+            let position = position.next().unwrap_or_default();
+            keep("actions", position, &action.key, &action.label)
+        });
+        let mut position = 0..;
+        def.exits.retain(|exit| {
+            let position = position.next().unwrap_or_default();
+            keep("exits", position, &exit.key, &exit.label)
+        });
+        match world.iter_mut().find(|existing| existing.id == def.id) {
+            Some(existing) => existing.merge(def),
+            None => {
+                let mut def = def;
+                if def.title.is_empty() {
+                    def.title = def.id.clone();
                 }
+                world.push(def);
             }
         }
-        Config::default()
     }
+    for (index, custom) in config.actions.custom().iter().enumerate() {
+        let label = &custom.label;
+        let key = custom.key.trim();
+        let key_path = format!("actions.custom.{index}.key");
+        if key.chars().count() != 1 {
+            conflicts.push(ConfigIssue::new(
+                &key_path,
+                format!("custom action \"{label}\" needs a single-character key, got \"{key}\""),
+            ));
+            continue;
+        }
+        if let Some(reserved) = reserved_hotkey(key) {
+            conflicts.push(ConfigIssue::new(
+                &key_path,
+                format!(
+                    "custom action \"{label}\" uses {reserved}, which is reserved as a global hotkey"
+                ),
+            ));
+            continue;
+        }
+        let Some(location) = world.iter_mut().find(|def| def.id == custom.location) else {
+            conflicts.push(ConfigIssue::new(
+                &format!("actions.custom.{index}.location"),
+                format!(
+                    "custom action \"{label}\" targets unknown location \"{}\"",
+                    custom.location
+                ),
+            ));
+            continue;
+        };
+        if let Some(taken) = location.hotkey_label(key) {
+            conflicts.push(ConfigIssue::new(
+                &key_path,
+                format!(
+                    "custom action \"{label}\" uses {} in {}, already taken by \"{taken}\"",
+                    key.to_uppercase(),
+                    location.id
+                ),
+            ));
+            continue;
+        }
+        if takes_process_panel_key(key) {
+            conflicts.push(process_panel_issue(&key_path, label, &location.id));
+        }
+        location.actions.push(LocationAction {
+            key: key.to_string(),
+            label: label.clone(),
+            action: None,
+            capture: custom.capture,
+            spec: custom.spec.clone(),
+        });
+    }
+    (world, conflicts)
 }
 
-#[derive(Default, Deserialize)]
-struct ActionsConfig {
+/// Whether `key` is `P`, which opens the Active processes panel unless the
+/// location's own menu uses it.
+fn takes_process_panel_key(key: &str) -> bool {
+    key.trim().eq_ignore_ascii_case("P")
+}
+
+/// The warning for a menu entry on `P`, which keeps the entry and hides the
+/// panel at that location.
+fn process_panel_issue(path: &str, label: &str, location: &str) -> ConfigIssue {
+    ConfigIssue::new(
+        path,
+        format!("\"{label}\" uses P in {location}, so the Active processes panel is hidden there"),
+    )
+}
+
+/// Returns the uppercase key when `key` is one of the global hotkeys.
+fn reserved_hotkey(key: &str) -> Option<&'static str> {
+    ["X", "Q"]
+        .into_iter()
+        .find(|reserved| key.trim().eq_ignore_ascii_case(reserved))
+}
+
+fn hotkey_matches(key: &str, choice: char) -> bool {
+    key.trim()
+        .chars()
+        .next()
+        .is_some_and(|first| first.to_ascii_lowercase() == choice)
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct SaveData {
+    version: u32,
+    #[serde(default)]
+    name: String,
+    level: u32,
+    xp: u32,
+    last_location: String,
+    created_at: u64,
+    updated_at: u64,
+    /// Names of the wardrobe items this operator owns.
     #[serde(default)]
-    search_tombs: Option<Vec<String>>,
+    clothing: Option<Vec<String>>,
+    /// Names of the owned wardrobe items currently worn.
     #[serde(default)]
-    check_mail: Option<Vec<String>>,
+    equipped: Option<Vec<String>>,
+    /// Built-in pockets this operator carries, as listed by saves written
+    /// before `[[character.pockets]]`; a built-in pocket missing from the list
+    /// stays out of reach. Configured pockets go by their `profiles` instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pockets: Option<Vec<String>>,
     #[serde(default)]
-    activate_screensaver: Option<Vec<String>>,
+    last_active_day: String,
     #[serde(default)]
-    computer_terminal: Option<Vec<String>>,
+    streak_days: u32,
+    /// How often each action (and `action:target` pair) has been performed.
     #[serde(default)]
-    lay_down: Option<Vec<String>>,
+    action_counts: BTreeMap<String, u32>,
+    /// Unlocked achievement ids mapped to their unlock time.
     #[serde(default)]
-    chest_tools: Vec<NamedCommand>,
+    achievements: BTreeMap<String, u64>,
+    /// When each action (or `action:target` pair) last paid out XP.
     #[serde(default)]
-    closet_launcher: Option<Vec<String>>,
+    xp_cooldowns: BTreeMap<String, u64>,
+    /// Actions (or `action:target` pairs) whose last run failed, with how.
     #[serde(default)]
-    explore_world: Option<Vec<String>>,
+    failed_actions: BTreeMap<String, String>,
     #[serde(default)]
-    grin_wallet: Option<Vec<String>>,
+    stats: CharacterStats,
 }
 
-impl ActionsConfig {
-    fn search_tombs_command(&self) -> Option<&[String]> {
-        self.search_tombs.as_deref().filter(|cmd| !cmd.is_empty())
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+struct CharacterStats {
+    hp: u32,
+    max_hp: u32,
+    strength: u32,
+    defense: u32,
+    credits: u32,
+    turns_left: u32,
+    /// Local date (`YYYY-MM-DD`) the turn budget was last refilled.
+    turns_day: String,
+}
+
+impl Default for CharacterStats {
+    fn default() -> Self {
+        Self {
+            hp: 20,
+            max_hp: 20,
+            strength: 10,
+            defense: 1,
+            credits: 0,
+            turns_left: 0,
+            turns_day: String::new(),
+        }
+    }
+}
+
+impl SaveData {
+    fn new(name: &str, config: &Config) -> Self {
+        let now = unix_timestamp();
+        Self {
+            version: SAVE_VERSION,
+            name: name.to_string(),
+            level: 1,
+            xp: 0,
+            last_location: START_LOCATION.to_string(),
+            created_at: now,
+            updated_at: now,
+            clothing: Some(default_clothing(config)),
+            equipped: None,
+            pockets: None,
+            last_active_day: String::new(),
+            streak_days: 0,
+            action_counts: BTreeMap::new(),
+            achievements: BTreeMap::new(),
+            xp_cooldowns: BTreeMap::new(),
+            failed_actions: BTreeMap::new(),
+            stats: CharacterStats::default(),
+        }
     }
 
-    fn check_mail_command(&self) -> Option<&[String]> {
-        self.check_mail.as_deref().filter(|cmd| !cmd.is_empty())
+    fn load(path: &Path) -> io::Result<Option<Self>> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let save: SaveData = toml::from_str(&contents).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("failed to parse {}: {err}", path.display()),
+            )
+        })?;
+        if save.version > SAVE_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} was written by a newer version (save format v{})",
+                    path.display(),
+                    save.version
+                ),
+            ));
+        }
+        Ok(Some(save))
     }
 
-    fn activate_screensaver_command(&self) -> Option<&[String]> {
-        self.activate_screensaver
-            .as_deref()
-            .filter(|cmd| !cmd.is_empty())
+    fn write(&mut self, path: &Path) -> io::Result<()> {
+        self.version = SAVE_VERSION;
+        let contents =
+            toml::to_string(self).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        write_atomic(path, contents.as_bytes())
     }
+}
 
-    fn computer_terminal_command(&self) -> Option<&[String]> {
-        self.computer_terminal
-            .as_deref()
-            .filter(|cmd| !cmd.is_empty())
+#[derive(Default, Deserialize)]
+struct Config {
+    #[serde(default)]
+    actions: ActionsConfig,
+    #[serde(default)]
+    character: CharacterConfig,
+    #[serde(default)]
+    achievements: Vec<AchievementDef>,
+    #[serde(default)]
+    xp: XpConfig,
+    #[serde(default)]
+    leveling: LevelingConfig,
+    #[serde(default)]
+    turns: TurnsConfig,
+    #[serde(default)]
+    locations: Vec<LocationDef>,
+}
+
+impl Config {
+    /// Reads every config layer, leaving out the ones that fail to parse or
+    /// would make the merged config invalid, and returns what went wrong for
+    /// the hub to show once its screen is up.
+    fn load() -> (Self, Vec<String>) {
+        let layers = ConfigLayers::read();
+        (layers.config(), layers.errors)
+    }
+}
+
+/// Arrays whose entries accumulate across config layers instead of being
+/// replaced. An entry whose `name` (or `id`) matches an earlier one takes its
+/// place; anything else is appended.
+const APPENDED_CONFIG_LISTS: &[&str] = &[
+    "actions.chest_tools",
+    "actions.custom",
+    "character.pockets",
+    "locations",
+    "achievements",
+];
+
+/// Every config file from `config_paths`, merged in order: tables merge key by
+/// key, later files win, and the lists in `APPENDED_CONFIG_LISTS` accumulate.
+/// `origins` remembers which file each leaf value came from.
+#[derive(Default)]
+struct ConfigLayers {
+    merged: toml::Table,
+    origins: BTreeMap<String, PathBuf>,
+    sources: Vec<PathBuf>,
+    errors: Vec<String>,
+}
+
+impl ConfigLayers {
+    fn read() -> Self {
+        let mut layers = ConfigLayers::default();
+        for path in config_paths() {
+            let Ok(contents) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let merged = contents
+                .parse::<toml::Table>()
+                .map_err(|err| format!("failed to parse: {err}"))
+                .and_then(|table| layers.merge(table, &path));
+            if let Err(err) = merged {
+                layers
+                    .errors
+                    .push(format!("Skipped {}, which {err}", path.display()));
+            }
+        }
+        layers
     }
 
-    fn lay_down_command(&self) -> Option<&[String]> {
-        self.lay_down.as_deref().filter(|cmd| !cmd.is_empty())
+    /// Merges `layer` in, unless the result would no longer be a valid
+    /// `Config`; then the layer is left out and the reason returned.
+    fn merge(&mut self, layer: toml::Table, source: &Path) -> Result<(), String> {
+        let mut merged = self.merged.clone();
+        let mut origins = self.origins.clone();
+        merge_config_table(&mut merged, layer, "", source, &mut origins);
+        if let Err(err) = toml::Value::Table(merged.clone()).try_into::<Config>() {
+            return Err(format!("is not a valid config: {err}"));
+        }
+        self.merged = merged;
+        self.origins = origins;
+        self.sources.push(source.to_path_buf());
+        Ok(())
     }
 
-    fn chest_tools(&self) -> &[NamedCommand] {
-        &self.chest_tools
+    fn config(&self) -> Config {
+        // `merge` only keeps layers that leave a valid config behind.
+        toml::Value::Table(self.merged.clone())
+            .try_into()
+            .unwrap_or_default()
     }
+}
 
-    fn closet_launcher_command(&self) -> Option<&[String]> {
-        self.closet_launcher
-            .as_deref()
-            .filter(|cmd| !cmd.is_empty())
+fn merge_config_table(
+    base: &mut toml::Table,
+    layer: toml::Table,
+    prefix: &str,
+    source: &Path,
+    origins: &mut BTreeMap<String, PathBuf>,
+) {
+    for (key, value) in layer {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(table)) => {
+                merge_config_table(existing, table, &path, source, origins);
+            }
+            (Some(toml::Value::Array(existing)), toml::Value::Array(entries))
+                if APPENDED_CONFIG_LISTS.contains(&path.as_str()) =>
+            {
+                for entry in entries {
+                    let index = existing
+                        .iter()
+                        .position(|current| same_config_entry(current, &entry))
+                        .unwrap_or(existing.len());
+                    let entry_path = format!("{path}[{index}]");
+                    forget_origins(origins, &entry_path);
+                    record_origins(origins, &entry_path, &entry, source);
+                    if index == existing.len() {
+                        existing.push(entry);
+                    } else {
+                        existing[index] = entry;
+                    }
+                }
+            }
+            (_, value) => {
+                forget_origins(origins, &path);
+                record_origins(origins, &path, &value, source);
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn same_config_entry(current: &toml::Value, entry: &toml::Value) -> bool {
+    ["name", "id"].iter().any(|field| {
+        let existing = current.get(field).and_then(toml::Value::as_str);
+        entry
+            .get(field)
+            .and_then(toml::Value::as_str)
+            .is_some_and(|name| existing == Some(name))
+    })
+}
+
+fn forget_origins(origins: &mut BTreeMap<String, PathBuf>, path: &str) {
+    origins.retain(|key, _| {
+        key != path
+            && !key.starts_with(&format!("{path}."))
+            && !key.starts_with(&format!("{path}["))
+    });
+}
+
+/// Records `source` for every leaf under `path`; command arrays count as one
+/// leaf, arrays of tables are indexed.
+fn record_origins(
+    origins: &mut BTreeMap<String, PathBuf>,
+    path: &str,
+    value: &toml::Value,
+    source: &Path,
+) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                record_origins(origins, &format!("{path}.{key}"), value, source);
+            }
+        }
+        toml::Value::Array(entries) if entries.iter().any(toml::Value::is_table) => {
+            for (index, entry) in entries.iter().enumerate() {
+                record_origins(origins, &format!("{path}[{index}]"), entry, source);
+            }
+        }
+        _ => {
+            origins.insert(path.to_string(), source.to_path_buf());
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct XpConfig {
+    /// Seconds before the same action (or chest tool/pocket) pays out again.
+    #[serde(default = "default_xp_cooldown")]
+    cooldown_secs: u64,
+    #[serde(default)]
+    cooldowns: BTreeMap<String, u64>,
+    /// Per-tool rewards for `[[actions.chest_tools]]`, keyed by tool name.
+    #[serde(default)]
+    chest_tools: BTreeMap<String, u32>,
+    /// Rewards keyed by action name, e.g. `check_mail = 5`.
+    #[serde(flatten)]
+    rewards: BTreeMap<String, u32>,
+}
+
+impl Default for XpConfig {
+    fn default() -> Self {
+        Self {
+            cooldown_secs: default_xp_cooldown(),
+            cooldowns: BTreeMap::new(),
+            chest_tools: BTreeMap::new(),
+            rewards: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_xp_cooldown() -> u64 {
+    60
+}
+
+impl XpConfig {
+    fn reward_for(&self, action: &str, target: Option<&str>) -> u32 {
+        if action == "chest"
+            && let Some(amount) = target.and_then(|tool| self.chest_tools.get(tool))
+        {
+            return *amount;
+        }
+        self.rewards
+            .get(action)
+            .copied()
+            .unwrap_or_else(|| default_action_xp(action))
+    }
+
+    fn cooldown_for(&self, action: &str) -> u64 {
+        self.cooldowns
+            .get(action)
+            .copied()
+            .unwrap_or(self.cooldown_secs)
+    }
+}
+
+fn default_action_xp(action: &str) -> u32 {
+    match action {
+        "search_tombs" | "chest" | "screensaver" => XP_MEDIUM,
+        _ => XP_SMALL,
+    }
+}
+
+#[derive(Deserialize)]
+struct TurnsConfig {
+    #[serde(default = "default_turns_per_day")]
+    per_day: u32,
+    /// Turn cost keyed by action name; unlisted actions cost one turn.
+    #[serde(default)]
+    costs: BTreeMap<String, u32>,
+}
+
+impl Default for TurnsConfig {
+    fn default() -> Self {
+        Self {
+            per_day: default_turns_per_day(),
+            costs: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_turns_per_day() -> u32 {
+    20
+}
+
+impl TurnsConfig {
+    fn cost_for(&self, action: &str) -> u32 {
+        self.costs.get(action).copied().unwrap_or(match action {
+            "lay_down" | "screensaver" => 0,
+            "search_tombs" => 2,
+            _ => 1,
+        })
+    }
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LevelCurve {
+    /// `base + (level - 1) * step`
+    #[default]
+    Linear,
+    /// `base * factor^(level - 1)`
+    Exponential,
+    /// Explicit per-level requirements from `thresholds`.
+    Table,
+}
+
+#[derive(Clone, Default, Deserialize)]
+struct LevelingConfig {
+    #[serde(default)]
+    curve: LevelCurve,
+    #[serde(default)]
+    base: Option<u32>,
+    #[serde(default)]
+    step: Option<u32>,
+    #[serde(default)]
+    factor: Option<f64>,
+    /// XP required to leave level 1, 2, 3, ...; the last entry repeats.
+    #[serde(default)]
+    thresholds: Vec<u32>,
+    #[serde(default)]
+    max_level: Option<u32>,
+    #[serde(default)]
+    titles: Vec<LevelTitle>,
+}
+
+#[derive(Clone, Deserialize)]
+struct LevelTitle {
+    min_level: u32,
+    title: String,
+}
+
+impl LevelingConfig {
+    fn threshold(&self, level: u32) -> Option<u32> {
+        if self.max_level.is_some_and(|max| level >= max) {
+            return None;
+        }
+        let steps = level.saturating_sub(1);
+        let threshold = match self.curve {
+            LevelCurve::Linear => self
+                .base
+                .unwrap_or(25)
+                .saturating_add(steps.saturating_mul(self.step.unwrap_or(10))),
+            LevelCurve::Exponential => {
+                let base = f64::from(self.base.unwrap_or(25));
+                let scaled = base * self.factor.unwrap_or(1.5).powi(steps as i32);
+                scaled.round().min(f64::from(u32::MAX)) as u32
+            }
+            LevelCurve::Table => self
+                .thresholds
+                .get(steps as usize)
+                .or(self.thresholds.last())
+                .copied()
+                .unwrap_or(25),
+        };
+        Some(threshold.max(1))
+    }
+
+    fn title_for(&self, level: u32) -> &str {
+        const DEFAULT_TITLES: [(u32, &str); 5] = [
+            (1, "Script Kiddie"),
+            (5, "Packet Runner"),
+            (10, "Console Cowboy"),
+            (20, "Ghost in the Wire"),
+            (35, "Netrunner Sovereign"),
+        ];
+        if self.titles.is_empty() {
+            return DEFAULT_TITLES
+                .iter()
+                .rev()
+                .find(|(min_level, _)| level >= *min_level)
+                .map_or("Operator", |(_, title)| title);
+        }
+        self.titles
+            .iter()
+            .filter(|band| level >= band.min_level)
+            .max_by_key(|band| band.min_level)
+            .map_or("Operator", |band| band.title.as_str())
+    }
+}
+
+#[derive(Default, Deserialize)]
+struct ActionsConfig {
+    #[serde(default, deserialize_with = "deserialize_command_spec")]
+    search_tombs: Option<CommandSpec>,
+    #[serde(default, deserialize_with = "deserialize_command_spec")]
+    check_mail: Option<CommandSpec>,
+    #[serde(default, deserialize_with = "deserialize_command_spec")]
+    activate_screensaver: Option<CommandSpec>,
+    #[serde(default, deserialize_with = "deserialize_command_spec")]
+    computer_terminal: Option<CommandSpec>,
+    #[serde(default, deserialize_with = "deserialize_command_spec")]
+    lay_down: Option<CommandSpec>,
+    #[serde(default)]
+    chest_tools: Vec<NamedCommand>,
+    #[serde(default, deserialize_with = "deserialize_command_spec")]
+    closet_launcher: Option<CommandSpec>,
+    #[serde(default, deserialize_with = "deserialize_command_spec")]
+    explore_world: Option<CommandSpec>,
+    #[serde(default, deserialize_with = "deserialize_command_spec")]
+    grin_wallet: Option<CommandSpec>,
+    #[serde(default)]
+    custom: Vec<CustomAction>,
+    /// Default time limit for captured commands, in seconds; zero disables it.
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+}
+
+impl ActionsConfig {
+    fn timeout_secs(&self) -> u64 {
+        self.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS)
+    }
+
+    fn search_tombs_command(&self) -> Option<&CommandSpec> {
+        self.search_tombs.as_ref().filter(|spec| !spec.is_empty())
+    }
+
+    fn check_mail_command(&self) -> Option<&CommandSpec> {
+        self.check_mail.as_ref().filter(|spec| !spec.is_empty())
+    }
+
+    fn activate_screensaver_command(&self) -> Option<&CommandSpec> {
+        self.activate_screensaver
+            .as_ref()
+            .filter(|spec| !spec.is_empty())
+    }
+
+    fn computer_terminal_command(&self) -> Option<&CommandSpec> {
+        self.computer_terminal
+            .as_ref()
+            .filter(|spec| !spec.is_empty())
+    }
+
+    fn lay_down_command(&self) -> Option<&CommandSpec> {
+        self.lay_down.as_ref().filter(|spec| !spec.is_empty())
+    }
+
+    fn chest_tools(&self) -> &[NamedCommand] {
+        &self.chest_tools
+    }
+
+    fn closet_launcher_command(&self) -> Option<&CommandSpec> {
+        self.closet_launcher
+            .as_ref()
+            .filter(|spec| !spec.is_empty())
+    }
+
+    fn explore_world_command(&self) -> Option<&CommandSpec> {
+        self.explore_world.as_ref().filter(|spec| !spec.is_empty())
+    }
+
+    fn grin_wallet_command(&self) -> Option<&CommandSpec> {
+        self.grin_wallet.as_ref().filter(|spec| !spec.is_empty())
+    }
+
+    fn custom(&self) -> &[CustomAction] {
+        &self.custom
+    }
+}
+
+/// An extra menu option from `[[actions.custom]]`, added to `location`
+/// (the safehouse unless stated otherwise).
+#[derive(Clone, Deserialize)]
+struct CustomAction {
+    key: String,
+    label: String,
+    /// Run to completion and print the output instead of spawning.
+    #[serde(default)]
+    capture: bool,
+    #[serde(default = "default_custom_location")]
+    location: String,
+    #[serde(flatten)]
+    spec: CommandSpec,
+}
+
+fn default_custom_location() -> String {
+    "room".to_string()
+}
+
+#[derive(Deserialize)]
+struct CharacterConfig {
+    #[serde(default)]
+    clothing: Vec<ClothingEntry>,
+    #[serde(default)]
+    pockets: Vec<PocketConfig>,
+    /// Whether the built-in Grin wallet pocket is carried.
+    #[serde(default = "default_true")]
+    default_pockets: bool,
+}
+
+impl Default for CharacterConfig {
+    fn default() -> Self {
+        Self {
+            clothing: Vec::new(),
+            pockets: Vec::new(),
+            default_pockets: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A `[character].clothing` entry: either a plain name (a cosmetic item, as in
+/// older configs) or a full wardrobe item table.
+#[derive(Clone, Deserialize)]
+#[serde(untagged)]
+enum ClothingEntry {
+    Name(String),
+    Item(WardrobeItem),
+}
+
+impl ClothingEntry {
+    fn into_item(self) -> WardrobeItem {
+        match self {
+            ClothingEntry::Name(name) => WardrobeItem::cosmetic(&name),
+            ClothingEntry::Item(item) => item,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum WardrobeSlot {
+    Head,
+    Body,
+    Feet,
+    Accessory,
+    /// Purely decorative; any number can be worn at once.
+    #[default]
+    Cosmetic,
+}
+
+impl WardrobeSlot {
+    fn label(self) -> &'static str {
+        match self {
+            WardrobeSlot::Head => "head",
+            WardrobeSlot::Body => "body",
+            WardrobeSlot::Feet => "feet",
+            WardrobeSlot::Accessory => "accessory",
+            WardrobeSlot::Cosmetic => "cosmetic",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl Rarity {
+    fn label(self) -> &'static str {
+        match self {
+            Rarity::Common => "common",
+            Rarity::Uncommon => "uncommon",
+            Rarity::Rare => "rare",
+            Rarity::Epic => "epic",
+            Rarity::Legendary => "legendary",
+        }
+    }
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+struct StatModifiers {
+    strength: i32,
+    defense: i32,
+    max_hp: i32,
+    /// Multiplies XP earned from actions while worn, e.g. `1.1` for +10%.
+    xp_multiplier: Option<f64>,
+}
+
+#[derive(Clone, Deserialize)]
+struct WardrobeItem {
+    name: String,
+    #[serde(default)]
+    slot: WardrobeSlot,
+    #[serde(default)]
+    rarity: Rarity,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    modifiers: StatModifiers,
+}
+
+impl WardrobeItem {
+    fn cosmetic(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            slot: WardrobeSlot::Cosmetic,
+            rarity: Rarity::Common,
+            description: String::new(),
+            modifiers: StatModifiers::default(),
+        }
+    }
+
+    fn gear(
+        name: &str,
+        slot: WardrobeSlot,
+        rarity: Rarity,
+        description: &str,
+        modifiers: StatModifiers,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            slot,
+            rarity,
+            description: description.to_string(),
+            modifiers,
+        }
+    }
+
+    fn summary(&self) -> String {
+        let mut bonuses = Vec::new();
+        let modifiers = &self.modifiers;
+        for (value, label) in [
+            (modifiers.strength, "STR"),
+            (modifiers.defense, "DEF"),
+            (modifiers.max_hp, "HP"),
+        ] {
+            if value != 0 {
+                bonuses.push(format!("{value:+} {label}"));
+            }
+        }
+        if let Some(multiplier) = modifiers.xp_multiplier {
+            bonuses.push(format!("x{multiplier} XP"));
+        }
+        let mut summary = format!(
+            "{} ({}, {})",
+            self.name,
+            self.slot.label(),
+            self.rarity.label()
+        );
+        if !bonuses.is_empty() {
+            summary.push_str(&format!(" {}", bonuses.join(", ")));
+        }
+        summary
+    }
+}
+
+/// Gear that can drop during play, followed by every configured clothing item;
+/// configured items replace drops with the same name.
+fn wardrobe_catalog(config: &Config) -> Vec<WardrobeItem> {
+    let mut catalog = vec![
+        WardrobeItem::gear(
+            "Crypt-dust Cloak",
+            WardrobeSlot::Body,
+            Rarity::Rare,
+            "Woven from the ash of decommissioned servers.",
+            StatModifiers {
+                defense: 2,
+                max_hp: 5,
+                ..StatModifiers::default()
+            },
+        ),
+        WardrobeItem::gear(
+            "Packet Sniffer Pendant",
+            WardrobeSlot::Accessory,
+            Rarity::Uncommon,
+            "Hums whenever unencrypted traffic passes nearby.",
+            StatModifiers {
+                strength: 2,
+                ..StatModifiers::default()
+            },
+        ),
+        WardrobeItem::gear(
+            "Overclocked Visor",
+            WardrobeSlot::Head,
+            Rarity::Epic,
+            "Runs hot, learns fast.",
+            StatModifiers {
+                xp_multiplier: Some(1.1),
+                ..StatModifiers::default()
+            },
+        ),
+        WardrobeItem::gear(
+            "Streak Runner Boots",
+            WardrobeSlot::Feet,
+            Rarity::Rare,
+            "Worn smooth by a week of daily patrols.",
+            StatModifiers {
+                defense: 1,
+                max_hp: 10,
+                ..StatModifiers::default()
+            },
+        ),
+    ];
+    for item in config
+        .character
+        .clothing
+        .iter()
+        .cloned()
+        .map(ClothingEntry::into_item)
+    {
+        match catalog
+            .iter_mut()
+            .find(|existing| existing.name == item.name)
+        {
+            Some(existing) => *existing = item,
+            None => catalog.push(item),
+        }
+    }
+    catalog
+}
+
+/// Resolves an owned item by name; items no longer in the catalog stay in the
+/// wardrobe as cosmetics rather than vanishing.
+fn wardrobe_item(config: &Config, name: &str) -> WardrobeItem {
+    wardrobe_catalog(config)
+        .into_iter()
+        .find(|item| item.name == name)
+        .unwrap_or_else(|| WardrobeItem::cosmetic(name))
+}
+
+/// A configured command and how to run it. `[actions]` entries take an
+/// argument array, a single string, or a table with `command` and the options
+/// below; chest tools, pockets, and custom or location actions take the same
+/// options next to their `command`.
+#[derive(Clone, Default, Deserialize)]
+struct CommandSpec {
+    #[serde(
+        rename = "command",
+        default,
+        deserialize_with = "deserialize_command_args"
+    )]
+    args: Vec<String>,
+    /// Working directory for the child.
+    #[serde(default)]
+    cwd: Option<String>,
+    /// Extra environment variables for the child.
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    /// Start the child with only `env` and the hub's `LORD_*` variables.
+    #[serde(default)]
+    clear_env: bool,
+    /// Run the first argument as an `sh -c` script; the rest become `$0`, `$1`, ...
+    #[serde(default)]
+    shell: bool,
+    /// How a launched (not captured) command shares the terminal; background
+    /// unless a TUI opts into the foreground.
+    #[serde(default)]
+    mode: LaunchMode,
+    /// Stop a captured command after this long; overrides `[actions] timeout_secs`.
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+    /// Exit codes that count as success; empty means just zero.
+    #[serde(default)]
+    ok_exit_codes: Vec<i32>,
+    /// Keys nothing else claimed. Entries flatten their spec in, so their
+    /// typos land here rather than with `serde_ignored`; `check-config`
+    /// reports them.
+    #[serde(flatten)]
+    unknown: toml::Table,
+}
+
+/// How a launched command shares the terminal with the hub.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum LaunchMode {
+    /// Hand the terminal to the child and wait for it to exit.
+    Foreground,
+    /// Run alongside the hub with output appended to a log file.
+    #[default]
+    Background,
+    /// Start a new session (`setsid`) so the child outlives the hub.
+    Detached,
+}
+
+impl CommandSpec {
+    fn is_empty(&self) -> bool {
+        self.args
+            .first()
+            .is_none_or(|program| program.trim().is_empty())
+    }
+
+    fn argv(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Whether `status` counts as success for this command.
+    fn accepts(&self, status: ExitStatus) -> bool {
+        exit_accepted(status, &self.ok_exit_codes)
+    }
+
+    /// The time limit for a captured run, falling back to `default_secs`;
+    /// zero means no limit.
+    fn timeout(&self, default_secs: u64) -> Option<Duration> {
+        Some(self.timeout_secs.unwrap_or(default_secs))
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+    }
+}
+
+/// Accepts `["prog", "arg"]` or a single string (one argument, or the whole
+/// script when `shell = true`).
+fn deserialize_command_args<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct ArgsVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for ArgsVisitor {
+        type Value = Vec<String>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a command string or an array of arguments")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            Ok(vec![value.to_string()])
+        }
+
+        fn visit_seq<A: serde::de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            Deserialize::deserialize(serde::de::value::SeqAccessDeserializer::new(seq))
+        }
+    }
+
+    deserializer.deserialize_any(ArgsVisitor)
+}
+
+/// Accepts the bare argument forms of `deserialize_command_args` or a full
+/// `CommandSpec` table.
+fn deserialize_command_spec<'de, D>(deserializer: D) -> Result<Option<CommandSpec>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct SpecVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for SpecVisitor {
+        type Value = CommandSpec;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a command string, an array of arguments, or a command table")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            Ok(CommandSpec {
+                args: vec![value.to_string()],
+                ..CommandSpec::default()
+            })
+        }
+
+        fn visit_seq<A: serde::de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            Ok(CommandSpec {
+                args: Deserialize::deserialize(serde::de::value::SeqAccessDeserializer::new(seq))?,
+                ..CommandSpec::default()
+            })
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            Deserialize::deserialize(serde::de::value::MapAccessDeserializer::new(map))
+        }
+    }
+
+    deserializer.deserialize_any(SpecVisitor).map(Some)
+}
+
+#[derive(Clone, Default, Deserialize)]
+struct NamedCommand {
+    #[serde(default)]
+    name: String,
+    #[serde(flatten)]
+    spec: CommandSpec,
+}
+
+struct Character {
+    name: String,
+    level: u32,
+    xp: u32,
+    wardrobe: Vec<WardrobeItem>,
+    equipped: Vec<String>,
+    pockets: Vec<PocketItem>,
+    leveling: LevelingConfig,
+    stats: CharacterStats,
+}
+
+impl Character {
+    fn new(config: &Config, save: &SaveData) -> Self {
+        let wardrobe: Vec<WardrobeItem> = save
+            .clothing
+            .clone()
+            .unwrap_or_else(|| default_clothing(config))
+            .iter()
+            .map(|name| wardrobe_item(config, name))
+            .collect();
+        let equipped = match &save.equipped {
+            Some(equipped) => equipped.clone(),
+            None => {
+                // Wear every cosmetic plus the first item for each slot.
+                let mut equipped: Vec<String> = Vec::new();
+                for item in &wardrobe {
+                    let taken = item.slot != WardrobeSlot::Cosmetic
+                        && wardrobe
+                            .iter()
+                            .any(|other| other.slot == item.slot && equipped.contains(&other.name));
+                    if !taken {
+                        equipped.push(item.name.clone());
+                    }
+                }
+                equipped
+            }
+        };
+
+        let name = if save.name.trim().is_empty() {
+            determine_character_name()
+        } else {
+            save.name.clone()
+        };
+
+        let pockets = pocket_catalog(config, &name, save.pockets.as_deref());
+
+        Self {
+            name,
+            level: save.level.max(1),
+            xp: save.xp,
+            wardrobe,
+            equipped,
+            pockets,
+            leveling: config.leveling.clone(),
+            stats: save.stats.clone(),
+        }
+    }
+
+    fn render_sheet(&self) {
+        println!(
+            "\n{}== Operator Dossier =={}",
+            paint(COLOR_TITLE),
+            paint(RESET)
+        );
+        println!(
+            "{}Name:{} {}",
+            paint(COLOR_OPTION_TEXT),
+            paint(RESET),
+            self.name
+        );
+        println!(
+            "{}Rank:{} {}",
+            paint(COLOR_OPTION_TEXT),
+            paint(RESET),
+            self.title()
+        );
+        let progress = match self.experience_to_next_level() {
+            Some(threshold) => format!(
+                "{} {}/{}",
+                render_progress_bar(self.xp, threshold),
+                self.xp,
+                threshold
+            ),
+            None => format!("{} MAX", render_progress_bar(1, 1)),
+        };
+        println!(
+            "{}Level:{} {}    {}XP:{} {}",
+            paint(COLOR_OPTION_TEXT),
+            paint(RESET),
+            self.level,
+            paint(COLOR_OPTION_TEXT),
+            paint(RESET),
+            progress
+        );
+        println!(
+            "{}HP:{} {}/{}    {}STR:{} {}    {}DEF:{} {}",
+            paint(COLOR_OPTION_TEXT),
+            paint(RESET),
+            self.stats.hp,
+            self.max_hp(),
+            paint(COLOR_OPTION_TEXT),
+            paint(RESET),
+            self.strength(),
+            paint(COLOR_OPTION_TEXT),
+            paint(RESET),
+            self.defense()
+        );
+        println!(
+            "{}Credits:{} {}    {}Turns today:{} {}",
+            paint(COLOR_OPTION_TEXT),
+            paint(RESET),
+            self.stats.credits,
+            paint(COLOR_OPTION_TEXT),
+            paint(RESET),
+            self.stats.turns_left
+        );
+        println!("{}Outfit:{}", paint(COLOR_OPTION_TEXT), paint(RESET));
+        let worn: Vec<&WardrobeItem> = self.equipped_items().collect();
+        if worn.is_empty() {
+            println!("  (nothing but a hospital gown)");
+        }
+        for item in worn {
+            println!("  - {}", item.summary());
+        }
+        println!("{}Pockets:{}", paint(COLOR_OPTION_TEXT), paint(RESET));
+        if self.pockets.is_empty() {
+            println!("  (empty)");
+        } else {
+            for (index, pocket) in self.pockets.iter().enumerate() {
+                println!("  [{}] {} — {}", index + 1, pocket.name, pocket.description);
+            }
+        }
+    }
+
+    fn gain_xp(&mut self, amount: u32) -> Option<u32> {
+        self.experience_to_next_level()?;
+        self.xp += amount;
+        let mut leveled = None;
+        while let Some(threshold) = self.experience_to_next_level() {
+            if self.xp < threshold {
+                break;
+            }
+            self.xp -= threshold;
+            self.level += 1;
+            self.stats.max_hp += 10;
+            self.stats.strength += 1;
+            self.stats.defense += 1;
+            self.stats.hp = self.max_hp();
+            leveled = Some(self.level);
+        }
+        if self.experience_to_next_level().is_none() {
+            self.xp = 0;
+        }
+        leveled
+    }
+
+    fn owns(&self, name: &str) -> bool {
+        self.wardrobe.iter().any(|item| item.name == name)
+    }
+
+    fn is_equipped(&self, name: &str) -> bool {
+        self.equipped.iter().any(|worn| worn == name)
+    }
+
+    fn equipped_items(&self) -> impl Iterator<Item = &WardrobeItem> {
+        self.wardrobe
+            .iter()
+            .filter(|item| self.is_equipped(&item.name))
+    }
+
+    /// Wears or removes the wardrobe item at `index`; wearing an item takes
+    /// its slot from whatever was there before.
+    fn toggle_equipped(&mut self, index: usize) {
+        let Some(item) = self.wardrobe.get(index) else {
+            return;
+        };
+        if self.is_equipped(&item.name) {
+            self.equipped.retain(|worn| worn != &item.name);
+        } else {
+            if item.slot != WardrobeSlot::Cosmetic {
+                let displaced: Vec<String> = self
+                    .equipped_items()
+                    .filter(|worn| worn.slot == item.slot)
+                    .map(|worn| worn.name.clone())
+                    .collect();
+                self.equipped.retain(|worn| !displaced.contains(worn));
+            }
+            self.equipped.push(item.name.clone());
+        }
+        self.stats.hp = self.stats.hp.min(self.max_hp());
+    }
+
+    fn stat_with_bonus(&self, base: u32, bonus: impl Fn(&StatModifiers) -> i32) -> u32 {
+        let total: i64 = i64::from(base)
+            + self
+                .equipped_items()
+                .map(|item| i64::from(bonus(&item.modifiers)))
+                .sum::<i64>();
+        total.clamp(0, i64::from(u32::MAX)) as u32
+    }
+
+    fn strength(&self) -> u32 {
+        self.stat_with_bonus(self.stats.strength, |mods| mods.strength)
+    }
+
+    fn defense(&self) -> u32 {
+        self.stat_with_bonus(self.stats.defense, |mods| mods.defense)
+    }
+
+    fn max_hp(&self) -> u32 {
+        self.stat_with_bonus(self.stats.max_hp, |mods| mods.max_hp)
+            .max(1)
+    }
+
+    fn xp_multiplier(&self) -> f64 {
+        self.equipped_items()
+            .filter_map(|item| item.modifiers.xp_multiplier)
+            .product()
+    }
+
+    /// XP needed to advance past the current level, or `None` at the level cap.
+    fn experience_to_next_level(&self) -> Option<u32> {
+        self.leveling.threshold(self.level)
+    }
+
+    fn title(&self) -> &str {
+        self.leveling.title_for(self.level)
+    }
+}
+
+fn render_progress_bar(value: u32, max: u32) -> String {
+    let filled = if max == 0 {
+        XP_BAR_WIDTH
+    } else {
+        (value.min(max) as usize * XP_BAR_WIDTH) / max as usize
+    };
+    format!(
+        "{}[{}{}{}{}]{}",
+        paint(COLOR_OPTION_TEXT),
+        paint(COLOR_OPTION_KEY),
+        "█".repeat(filled),
+        paint(COLOR_OPTION_TEXT),
+        "░".repeat(XP_BAR_WIDTH - filled),
+        paint(RESET)
+    )
+}
+
+fn default_clothing(config: &Config) -> Vec<String> {
+    if config.character.clothing.is_empty() {
+        vec![
+            String::from("Aurora-weave jacket"),
+            String::from("Carbon-thread boots"),
+            String::from("Holographic lapel pin"),
+        ]
+    } else {
+        config
+            .character
+            .clothing
+            .iter()
+            .cloned()
+            .map(|entry| entry.into_item().name)
+            .collect()
+    }
+}
+
+/// Pockets carried by `operator`: the default Grin wallet (unless disabled,
+/// or left out of an older save's `carried` list) followed by
+/// `[[character.pockets]]`; an entry with the same name replaces the default.
+fn pocket_catalog(config: &Config, operator: &str, carried: Option<&[String]>) -> Vec<PocketItem> {
+    let mut pockets = Vec::new();
+    if config.character.default_pockets {
+        let wallet = PocketItem::grin_wallet(config.actions.grin_wallet_command());
+        if carried.is_none_or(|carried| carried.contains(&wallet.name)) {
+            pockets.push(wallet);
+        }
+    }
+    for entry in &config.character.pockets {
+        if entry.name.trim().is_empty() {
+            continue;
+        }
+        let carried = entry.profiles.is_empty()
+            || entry
+                .profiles
+                .iter()
+                .any(|profile| profile.eq_ignore_ascii_case(operator));
+        if !carried {
+            continue;
+        }
+        let item = PocketItem {
+            name: entry.name.clone(),
+            description: entry.description.clone(),
+            command: Some(entry.spec.clone()).filter(|spec| !spec.is_empty()),
+            capture: entry.capture,
+        };
+        match pockets.iter_mut().find(|pocket| pocket.name == item.name) {
+            Some(existing) => *existing = item,
+            None => pockets.push(item),
+        }
+    }
+    pockets
+}
+
+#[derive(Clone, Deserialize)]
+struct PocketConfig {
+    name: String,
+    #[serde(default)]
+    description: String,
+    /// Run the command to completion and show its output instead of spawning it.
+    #[serde(default)]
+    capture: bool,
+    /// Operator profiles that carry this pocket; empty means everyone.
+    #[serde(default)]
+    profiles: Vec<String>,
+    #[serde(flatten)]
+    spec: CommandSpec,
+}
+
+struct PocketItem {
+    name: String,
+    description: String,
+    command: Option<CommandSpec>,
+    capture: bool,
+}
+
+impl PocketItem {
+    fn grin_wallet(command: Option<&CommandSpec>) -> Self {
+        let command = command.cloned().unwrap_or_else(|| CommandSpec {
+            args: vec![String::from("grin-wallet")],
+            mode: LaunchMode::Foreground,
+            ..CommandSpec::default()
+        });
+        Self {
+            name: String::from("Grin Wallet"),
+            description: String::from("Shielded grin-wallet client"),
+            command: Some(command),
+            capture: false,
+        }
+    }
+
+    fn use_item(&self, default_timeout_secs: u64) -> ActionOutcome {
+        match &self.command {
+            Some(cmd) if self.capture => {
+                match stream_command(cmd, cmd.timeout(default_timeout_secs)) {
+                    Ok(run) => {
+                        if !run.printed {
+                            println!("The command completed without output.");
+                        }
+                        exit_outcome(cmd, run.status)
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {
+                        println!("{} was {err}.", self.name);
+                        ActionOutcome::Cancelled
+                    }
+                    Err(err) => {
+                        eprintln!("{} refuses to activate: {err}", self.name);
+                        ActionOutcome::from_stream_error(&err)
+                    }
+                }
+            }
+            Some(cmd) => launch_command(cmd, &format!("{} refuses to activate", self.name)),
+            None => {
+                println!("This pocket item is ornamental only.");
+                ActionOutcome::Unconfigured
+            }
+        }
+    }
+}
+
+impl NamedCommand {
+    fn is_valid(&self) -> bool {
+        !self.name.trim().is_empty() && !self.spec.is_empty()
+    }
+}
+
+fn show_location(location: &LocationDef) {
+    println!();
+    print_centered_colored(&format!("== {} ==", location.title), COLOR_TITLE);
+    for line in location.art.lines() {
+        print_centered_colored(line, COLOR_ART);
+    }
+}
+
+enum ActionOutcome {
+    /// A configured command was launched and left running with this PID.
+    Spawned(u32),
+    /// A configured command ran to completion, captured or in the foreground.
+    Completed,
+    /// No command was configured, so the hub's built-in behavior ran.
+    Builtin,
+    /// No command was configured and there is nothing built in to fall back on.
+    Unconfigured,
+    Failed(String),
+    /// A captured command ran past its time limit and was stopped.
+    TimedOut(String),
+    /// The operator stopped a captured command with Ctrl-C.
+    Cancelled,
+}
+
+impl ActionOutcome {
+    fn label(&self) -> &'static str {
+        match self {
+            ActionOutcome::Spawned(_) => "spawned",
+            ActionOutcome::Completed => "completed",
+            ActionOutcome::Builtin => "builtin",
+            ActionOutcome::Unconfigured => "unconfigured",
+            ActionOutcome::Failed(_) => "failed",
+            ActionOutcome::TimedOut(_) => "timed_out",
+            ActionOutcome::Cancelled => "cancelled",
+        }
+    }
+
+    fn succeeded(&self) -> bool {
+        matches!(
+            self,
+            ActionOutcome::Spawned(_) | ActionOutcome::Completed | ActionOutcome::Builtin
+        )
+    }
+
+    /// Keeps a timed-out or cancelled `stream_command` apart from one that
+    /// failed to run.
+    fn from_stream_error(err: &io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::TimedOut => ActionOutcome::TimedOut(err.to_string()),
+            io::ErrorKind::Interrupted => ActionOutcome::Cancelled,
+            _ => ActionOutcome::Failed(err.to_string()),
+        }
+    }
+
+    fn error(&self) -> Option<&str> {
+        match self {
+            ActionOutcome::Failed(err) | ActionOutcome::TimedOut(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct JournalEntry {
+    timestamp: String,
+    profile: String,
+    action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<String>,
+    location: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    command: Option<Vec<String>>,
+    outcome: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    xp: u32,
+}
+
+impl JournalEntry {
+    fn date(&self) -> &str {
+        self.timestamp.get(..10).unwrap_or(&self.timestamp)
+    }
+
+    fn summary_line(&self) -> String {
+        let time = self.timestamp.get(..16).unwrap_or(&self.timestamp);
+        let action = match &self.target {
+            Some(target) => format!("{} ({target})", self.action),
+            None => self.action.clone(),
+        };
+        let mut line = format!(
+            "{} {:<12} {:<24} {:<12} +{} XP",
+            time.replacen('T', " ", 1),
+            self.location,
+            action,
+            self.outcome,
+            self.xp
+        );
+        if let Some(err) = &self.error {
+            line.push_str(&format!(" — {err}"));
+        }
+        line
+    }
+}
+
+/// Append-only JSON Lines log of every action launched from the hub.
+struct Journal {
+    path: Option<PathBuf>,
+}
+
+impl Journal {
+    fn open() -> Self {
+        Self {
+            path: data_dir().map(|dir| dir.join("journal.jsonl")),
+        }
+    }
+
+    fn append(&self, entry: &JournalEntry) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_string(entry)
+            .map_err(io::Error::from)
+            .and_then(|mut line| {
+                line.push('\n');
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?
+                    .write_all(line.as_bytes())
+            });
+        if let Err(err) = result {
+            eprintln!("Failed to write journal entry to {}: {err}", path.display());
+        }
+    }
+
+    fn read_entries(&self) -> Vec<JournalEntry> {
+        let Some(contents) = self
+            .path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+        else {
+            return Vec::new();
+        };
+        contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+}
+
+fn run_journal_command(query: &JournalQuery, profile: Option<&str>) -> i32 {
+    let journal = Journal::open();
+    let Some(path) = &journal.path else {
+        eprintln!("Cannot locate the journal: neither XDG_DATA_HOME nor HOME is set.");
+        return 1;
+    };
+    if !path.exists() {
+        println!("No journal recorded yet at {}.", path.display());
+        return 0;
+    }
+    for entry in journal.read_entries() {
+        let matches = query
+            .since
+            .as_deref()
+            .is_none_or(|since| entry.date() >= since)
+            && query
+                .until
+                .as_deref()
+                .is_none_or(|until| entry.date() <= until)
+            && query
+                .action
+                .as_deref()
+                .is_none_or(|action| entry.action.eq_ignore_ascii_case(action))
+            && profile.is_none_or(|name| entry.profile.eq_ignore_ascii_case(name.trim()));
+        if matches {
+            let profile = if profile.is_some() {
+                String::new()
+            } else {
+                format!("{:<16} ", entry.profile)
+            };
+            println!("{profile}{}", entry.summary_line());
+        }
+    }
+    0
+}
+
+fn parse_date_arg(flag: &str, value: String) -> Result<String, String> {
+    let bytes = value.as_bytes();
+    let well_formed = bytes.len() == 10
+        && bytes.iter().enumerate().all(|(index, byte)| match index {
+            4 | 7 => *byte == b'-',
+            _ => byte.is_ascii_digit(),
+        });
+    if well_formed {
+        Ok(value)
+    } else {
+        Err(format!(
+            "{flag} expects a date like 2024-01-31, got {value}"
+        ))
+    }
+}
+
+/// A config problem tied to the TOML key it concerns, so `check-config` can
+/// point at the offending line.
+struct ConfigIssue {
+    path: Vec<String>,
+    message: String,
+}
+
+impl ConfigIssue {
+    /// `path` is dotted, with array entries as indices: `actions.custom.0.key`.
+    fn new(path: &str, message: String) -> Self {
+        Self {
+            path: path.split('.').map(String::from).collect(),
+            message,
+        }
+    }
+}
+
+fn run_check_config(path: Option<&Path>) -> i32 {
+    let paths: Vec<PathBuf> = match path {
+        Some(path) => vec![path.to_path_buf()],
+        None => config_paths()
+            .into_iter()
+            .filter(|path| path.exists())
+            .collect(),
+    };
+    if paths.is_empty() {
+        println!("No config file found; the built-in defaults apply.");
+        return 0;
+    }
+    let mut problems = 0;
+    // Each readable file with its contents and problems (by byte offset).
+    let mut files = Vec::new();
+    for path in &paths {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                let issues = check_config_source(&contents);
+                files.push((path.clone(), contents, issues));
+            }
+            Err(err) => {
+                println!("{}: cannot read: {err}", path.display());
+                problems += 1;
+            }
+        }
+    }
+    // A custom action may target a location from another layer, and a later
+    // layer may replace a command, so these checks run on the merged result.
+    let mut layers = ConfigLayers::default();
+    for (path, contents, _) in &files {
+        if let Ok(table) = contents.parse::<toml::Table>() {
+            // A layer that does not merge was already reported on its own.
+            let _ = layers.merge(table, path);
+        }
+    }
+    let mut unplaced = Vec::new();
+    for issue in check_merged_config(&layers.config(), &layers.merged) {
+        let Some((source, path)) = locate_merged_issue(&layers, &issue.path) else {
+            unplaced.push(issue.message);
+            continue;
+        };
+        let Some((_, contents, issues)) = files.iter_mut().find(|(file, ..)| *file == source)
+        else {
+            unplaced.push(issue.message);
+            continue;
+        };
+        let offset = toml_edit::ImDocument::parse(contents.as_str())
+            .map_or(0, |document| key_offset(&document, &path));
+        issues.push((offset, issue.message));
+    }
+    for (path, contents, issues) in &mut files {
+        if issues.is_empty() {
+            println!("{}: ok", path.display());
+        }
+        issues.sort_by_key(|(offset, _)| *offset);
+        for (offset, message) in issues.iter() {
+            let (line, column) = line_and_column(contents, *offset);
+            println!("{}:{line}:{column}: {message}", path.display());
+        }
+        problems += issues.len();
+    }
+    for message in &unplaced {
+        println!("merged config: {message}");
+    }
+    problems += unplaced.len();
+    if problems == 0 {
+        return 0;
+    }
+    println!(
+        "{problems} problem{} found.",
+        if problems == 1 { "" } else { "s" }
+    );
+    1
+}
+
+/// Lints one config file on its own (syntax, unknown keys, and chest tool
+/// names), returning each problem with its byte offset.
+fn check_config_source(contents: &str) -> Vec<(usize, String)> {
+    let mut unknown = Vec::new();
+    let parsed = serde_ignored::deserialize(toml::Deserializer::new(contents), |path| {
+        unknown.push(ignored_key_path(&path));
+    });
+    let config: Config = match parsed {
+        Ok(config) => config,
+        Err(err) => {
+            let offset = err.span().map_or(0, |span| span.start);
+            let message: Vec<&str> = err.message().lines().map(str::trim).collect();
+            return vec![(offset, message.join(": "))];
+        }
+    };
+    let Ok(document) = toml_edit::ImDocument::parse(contents) else {
+        return Vec::new();
+    };
+    let mut issues: Vec<ConfigIssue> = unknown
+        .into_iter()
+        .map(|path| ConfigIssue {
+            message: format!("unknown key `{}`", display_key_path(&path)),
+            path,
+        })
+        .collect();
+    // The trigger is flattened into each achievement, which hides its typos
+    // from `serde_ignored`.
+    let table: toml::Table = contents.parse().unwrap_or_default();
+    let achievements = table.get("achievements").and_then(toml::Value::as_array);
+    for (index, entry) in achievements.into_iter().flatten().enumerate() {
+        let Some(entry) = entry.as_table() else {
+            continue;
+        };
+        let trigger = entry.get("trigger").and_then(toml::Value::as_str);
+        let known = AchievementTrigger::keys(trigger.unwrap_or_default());
+        for key in entry.keys() {
+            if !ACHIEVEMENT_KEYS.contains(&key.as_str()) && !known.contains(&key.as_str()) {
+                let mut issue =
+                    ConfigIssue::new(&format!("achievements.{index}.{key}"), String::new());
+                issue.message = format!("unknown key `{}`", display_key_path(&issue.path));
+                issues.push(issue);
+            }
+        }
+    }
+
+    let mut seen_tools: Vec<&str> = Vec::new();
+    for (index, tool) in config.actions.chest_tools.iter().enumerate() {
+        let name = tool.name.trim();
+        if name.is_empty() {
+            issues.push(ConfigIssue::new(
+                &format!("actions.chest_tools.{index}"),
+                String::from("chest tool has no name and is hidden from the chest"),
+            ));
+        } else if seen_tools.contains(&name) {
+            issues.push(ConfigIssue::new(
+                &format!("actions.chest_tools.{index}.name"),
+                format!("duplicate chest tool name \"{name}\""),
+            ));
+        } else {
+            seen_tools.push(name);
+        }
+    }
+    for (path, command) in config_commands(&config) {
+        for key in command.unknown.keys() {
+            let mut issue = ConfigIssue::new(&format!("{path}.{key}"), String::new());
+            issue.message = format!("unknown key `{}`", display_key_path(&issue.path));
+            issues.push(issue);
+        }
+    }
+    // `[xp]` rewards share their table with the settings, so a misspelt
+    // action would otherwise pass as a reward nothing ever claims.
+    for action in config.xp.rewards.keys() {
+        if !JOURNAL_ACTIONS.contains(&action.as_str()) {
+            issues.push(ConfigIssue::new(
+                &format!("xp.{action}"),
+                format!(
+                    "unknown key `xp.{action}`; rewards are keyed by action: {}",
+                    JOURNAL_ACTIONS.join(", ")
+                ),
+            ));
+        }
+    }
+
+    let mut located: Vec<(usize, String)> = issues
+        .into_iter()
+        .map(|issue| (key_offset(&document, &issue.path), issue.message))
+        .collect();
+    located.sort_by_key(|(offset, _)| *offset);
+    located
+}
+
+/// Every command in `config`, with the key path of the table (or value)
+/// defining it.
+fn config_commands(config: &Config) -> Vec<(String, CommandSpec)> {
+    let actions = &config.actions;
+    let mut commands: Vec<(String, CommandSpec)> = [
+        ("search_tombs", &actions.search_tombs),
+        ("check_mail", &actions.check_mail),
+        ("activate_screensaver", &actions.activate_screensaver),
+        ("computer_terminal", &actions.computer_terminal),
+        ("lay_down", &actions.lay_down),
+        ("closet_launcher", &actions.closet_launcher),
+        ("explore_world", &actions.explore_world),
+        ("grin_wallet", &actions.grin_wallet),
+    ]
+    .into_iter()
+    .filter_map(|(name, command)| Some((format!("actions.{name}"), command.clone()?)))
+    .collect();
+    for (index, tool) in actions.chest_tools.iter().enumerate() {
+        commands.push((format!("actions.chest_tools.{index}"), tool.spec.clone()));
+    }
+    for (index, custom) in actions.custom.iter().enumerate() {
+        commands.push((format!("actions.custom.{index}"), custom.spec.clone()));
+    }
+    for (index, pocket) in config.character.pockets.iter().enumerate() {
+        commands.push((format!("character.pockets.{index}"), pocket.spec.clone()));
+    }
+    for (index, location) in config.locations.iter().enumerate() {
+        for (position, action) in location.actions.iter().enumerate() {
+            commands.push((
+                format!("locations.{index}.actions.{position}"),
+                action.spec.clone(),
+            ));
+        }
+    }
+    commands
+}
+
+/// The checks that need every layer at once (commands, working directories,
+/// exits, and hotkeys), run on the merged config. Issue paths point into
+/// `merged`.
+fn check_merged_config(config: &Config, merged: &toml::Table) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    let mut vars = BTreeMap::new();
+    if let Some(dir) = data_dir() {
+        vars.insert(String::from("LORD_DATA_DIR"), dir.display().to_string());
+    }
+    for (path, command) in config_commands(config) {
+        // Pockets and actions are tables that may leave `command` out entirely;
+        // a plain `check_mail = []` is an empty command all the same.
+        let left_out = lookup_key(merged, &path)
+            .and_then(toml::Value::as_table)
+            .is_some_and(|entry| !entry.contains_key("command"));
+        if command.args.is_empty() && left_out {
+            continue;
+        }
+        let raw_program = command.args.first().map_or("", |arg| arg.trim());
+        let program = expand_command_arg(raw_program, &vars);
+        if command.is_empty() {
+            issues.push(ConfigIssue::new(
+                &format!("{path}.command"),
+                String::from("empty command is ignored"),
+            ));
+        } else if !command.shell && !uses_profile_var(raw_program) && !program_on_path(&program) {
+            issues.push(ConfigIssue::new(
+                &format!("{path}.command"),
+                format!("`{program}` is not an executable on PATH"),
+            ));
+        }
+        if let Some(cwd) = command.cwd.as_ref().filter(|cwd| !uses_profile_var(cwd)) {
+            let dir = expand_command_arg(cwd, &vars);
+            if !Path::new(&dir).is_dir() {
+                issues.push(ConfigIssue::new(
+                    &format!("{path}.cwd"),
+                    format!("working directory `{dir}` does not exist"),
+                ));
+            }
+        }
+    }
+    let (world, conflicts) = assemble_world(config);
+    issues.extend(conflicts);
+    for (index, location) in config.locations.iter().enumerate() {
+        for (position, exit) in location.exits.iter().enumerate() {
+            if !world.iter().any(|def| def.id == exit.to) {
+                issues.push(ConfigIssue::new(
+                    &format!("locations.{index}.exits.{position}.to"),
+                    format!(
+                        "exit \"{}\" in {} leads to unknown location \"{}\"",
+                        exit.label, location.id, exit.to
+                    ),
+                ));
+            }
+        }
+    }
+    issues
+}
+
+/// Whether `arg` uses a `LORD_*` variable that depends on the profile, so
+/// `check-config` cannot tell what it expands to.
+fn uses_profile_var(arg: &str) -> bool {
+    ["LORD_PROFILE", "LORD_LEVEL"]
+        .iter()
+        .any(|name| arg.contains(&format!("${name}")) || arg.contains(&format!("${{{name}")))
+}
+
+/// Finds the layer that set the merged key `path` and the same key's path
+/// within that file, where entries of the lists in `APPENDED_CONFIG_LISTS`
+/// may sit at another index.
+fn locate_merged_issue(layers: &ConfigLayers, path: &[String]) -> Option<(PathBuf, Vec<String>)> {
+    let mut prefix = path.to_vec();
+    let source = loop {
+        let key = display_key_path(&prefix);
+        let found = layers.origins.iter().find(|(origin, _)| {
+            **origin == key
+                || origin.starts_with(&format!("{key}."))
+                || origin.starts_with(&format!("{key}["))
+        });
+        if let Some((_, source)) = found {
+            break source.clone();
+        }
+        prefix.pop()?;
+    };
+    let mut local = path.to_vec();
+    for list in APPENDED_CONFIG_LISTS {
+        let depth = list.split('.').count();
+        if path.len() <= depth
+            || !path
+                .iter()
+                .map(String::as_str)
+                .take(depth)
+                .eq(list.split('.'))
+        {
+            continue;
+        }
+        let entry = lookup_key(&layers.merged, &path[..=depth].join("."));
+        let table: toml::Table = fs::read_to_string(&source).ok()?.parse().ok()?;
+        let index = lookup_key(&table, list)?
+            .as_array()?
+            .iter()
+            .position(|candidate| Some(candidate) == entry)?;
+        local[depth] = index.to_string();
+    }
+    Some((source, local))
+}
+
+fn run_config_show(origin: bool) -> i32 {
+    let layers = ConfigLayers::read();
+    for error in &layers.errors {
+        eprintln!("{error}");
+    }
+    if layers.sources.is_empty() {
+        println!("# No config files found; the built-in defaults apply.");
+    }
+    for source in &layers.sources {
+        println!("# layer: {}", source.display());
+    }
+    if origin {
+        print_config_origins(&layers, &toml::Value::Table(layers.merged.clone()), "");
+    } else {
+        match toml::to_string(&layers.merged) {
+            Ok(rendered) => print!("{rendered}"),
+            Err(err) => {
+                eprintln!("Cannot render the config: {err}");
+                return 1;
+            }
+        }
+    }
+    let (_, skipped) = assemble_world(&layers.config());
+    for issue in &skipped {
+        eprintln!("Skipped: {}", issue.message);
+    }
+    if layers.errors.is_empty() && skipped.is_empty() {
+        0
+    } else {
+        1
+    }
+}
+
+/// Prints one `key = value  # file` line per leaf of the merged config.
+fn print_config_origins(layers: &ConfigLayers, value: &toml::Value, path: &str) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                print_config_origins(layers, value, &child);
+            }
+        }
+        toml::Value::Array(entries) if entries.iter().any(toml::Value::is_table) => {
+            for (index, entry) in entries.iter().enumerate() {
+                print_config_origins(layers, entry, &format!("{path}[{index}]"));
+            }
+        }
+        _ => {
+            let origin = layers
+                .origins
+                .get(path)
+                .map_or_else(|| String::from("?"), |source| source.display().to_string());
+            println!("{path} = {value}  # {origin}");
+        }
+    }
+}
+
+/// What `lord init` proposes for each `[actions]` field: a comment for the
+/// generated file and candidate commands, the first one found on `PATH` wins.
+const INIT_ACTIONS: &[(&str, &str, &[&[&str]])] = &[
+    (
+        "search_tombs",
+        "Graveyard [S]: list tomb vaults; the output is shown in the hub.",
+        &[&["tomb", "list"]],
+    ),
+    (
+        "check_mail",
+        "Safehouse [M]: your mail client.",
+        &[
+            &["thunderbird"],
+            &["evolution"],
+            &["geary"],
+            &["neomutt"],
+            &["mutt"],
+        ],
+    ),
+    (
+        "computer_terminal",
+        "Safehouse [C]: a VM manager or terminal.",
+        &[
+            &["virt-manager"],
+            &["vmware"],
+            &["virtualbox"],
+            &["gnome-boxes"],
+        ],
+    ),
+    (
+        "closet_launcher",
+        "Safehouse [O]: a game launcher.",
+        &[&["steam"], &["lutris"], &["heroic"], &["retroarch"]],
+    ),
+    (
+        "explore_world",
+        "Safehouse [E]: a web browser.",
+        &[
+            &["firefox"],
+            &["librewolf"],
+            &["chromium"],
+            &["google-chrome"],
+            &["brave-browser"],
+        ],
+    ),
+    (
+        "lay_down",
+        "Safehouse [L]: lock the screen for a short rest.",
+        &[
+            &["swaylock"],
+            &["i3lock"],
+            &["slock"],
+            &["xdg-screensaver", "lock"],
+        ],
+    ),
+    (
+        "activate_screensaver",
+        "Safehouse [B]: a screensaver; leave unset for the built-in one.",
+        &[
+            &["xscreensaver-command", "-activate"],
+            &["cmatrix", "-B", "-s"],
+        ],
+    ),
+    (
+        "grin_wallet",
+        "Dossier pocket: the Grin wallet.",
+        &[&["grin-wallet", "info"]],
+    ),
+];
+
+/// Chest tools `lord init` offers when their program is on `PATH`.
+const INIT_CHEST_TOOLS: &[(&str, &[&str])] = &[
+    ("WireGuard", &["wg-quick", "up", "wg0"]),
+    ("Nmap Sweep", &["nmap", "-sV", "192.168.1.0/24"]),
+    ("Suricata", &["suricata", "-D"]),
+    ("Wireshark", &["wireshark"]),
+];
+
+/// Terminal apps `lord init` writes with `mode = "foreground"` so they get the
+/// terminal to themselves.
+const INIT_FOREGROUND_PROGRAMS: &[&str] = &["neomutt", "mutt", "cmatrix", "grin-wallet"];
+
+/// Desktop apps `lord init` writes with `mode = "detached"` so they outlive
+/// the hub.
+const INIT_DETACHED_PROGRAMS: &[&str] = &[
+    "thunderbird",
+    "evolution",
+    "geary",
+    "virt-manager",
+    "vmware",
+    "virtualbox",
+    "gnome-boxes",
+    "steam",
+    "lutris",
+    "heroic",
+    "retroarch",
+    "firefox",
+    "librewolf",
+    "chromium",
+    "google-chrome",
+    "brave-browser",
+    "wireshark",
+];
+
+fn run_init(force: bool) -> i32 {
+    let Some(path) = config_dir().map(|dir| dir.join("config.toml")) else {
+        eprintln!("Cannot locate the config directory: neither XDG_CONFIG_HOME nor HOME is set.");
+        return 1;
+    };
+    if path.exists() && !force {
+        eprintln!(
+            "{} already exists. Pass --force to replace it.",
+            path.display()
+        );
+        return 1;
+    }
+    match init_wizard() {
+        Ok(contents) => match write_atomic(&path, contents.as_bytes()) {
+            Ok(()) => {
+                println!(
+                    "Wrote {}. Check it any time with `lord check-config`.",
+                    path.display()
+                );
+                0
+            }
+            Err(err) => {
+                eprintln!("Failed to write {}: {err}", path.display());
+                1
+            }
+        },
+        Err(err) => {
+            eprintln!("An error occurred: {err}");
+            1
+        }
+    }
+}
+
+/// Walks through each action, proposing a detected command, and returns the
+/// commented config text. ENTER accepts a proposal, `-` leaves the action
+/// unset, and anything else is taken as the command (split on whitespace).
+/// At end of input the remaining proposals are accepted as-is.
+fn init_wizard() -> io::Result<String> {
+    println!("Setting up Legend of the Red Desktop. Press ENTER to accept a proposal,");
+    println!("type a command to use instead, or `-` to leave an action unset.\n");
+    let mut at_eof = false;
+    let mut ask = |question: String| -> io::Result<Option<String>> {
+        if at_eof {
+            return Ok(None);
+        }
+        println!("{question}");
+        let answer = read_line_trimmed()?;
+        at_eof = answer.is_none();
+        Ok(answer.filter(|answer| !answer.is_empty()))
+    };
+    let render = |command: &[&str]| {
+        toml::Value::Array(command.iter().map(|arg| toml::Value::from(*arg)).collect()).to_string()
+    };
+    let mode = |command: &[&str]| {
+        if INIT_FOREGROUND_PROGRAMS.contains(&command[0]) {
+            Some("foreground")
+        } else if INIT_DETACHED_PROGRAMS.contains(&command[0]) {
+            Some("detached")
+        } else {
+            None
+        }
+    };
+    let render_spec = |command: &[&str]| match mode(command) {
+        Some(mode) => format!("{{ command = {}, mode = \"{mode}\" }}", render(command)),
+        None => render(command),
+    };
+
+    let mut contents = format!(
+        "# Legend of the Red Desktop config, generated by `lord init` on {}.\n\
+         # Layers load in order: /etc/lord/config.toml, this file, conf.d/*.toml,\n\
+         # then ./lord_config.toml. Run `lord check-config` after editing.\n\n[actions]\n",
+        &local_timestamp(unix_timestamp())[..10]
+    );
+    for (field, comment, candidates) in INIT_ACTIONS {
+        let detected = candidates
+            .iter()
+            .find(|command| program_on_path(command[0]))
+            .copied();
+        let proposal = detected.map(render_spec);
+        let question = match &proposal {
+            Some(proposal) => format!("{field} (found {proposal}):"),
+            None => format!("{field} (nothing detected; ENTER to skip):"),
+        };
+        let answer = ask(question)?;
+        contents.push_str(&format!("\n# {comment}\n"));
+        let chosen = match answer.as_deref() {
+            Some("-") => None,
+            Some(custom) => Some(render_spec(&custom.split_whitespace().collect::<Vec<_>>())),
+            None => proposal,
+        };
+        match chosen {
+            Some(command) => contents.push_str(&format!("{field} = {command}\n")),
+            None => contents.push_str(&format!("# {field} = {}\n", render_spec(candidates[0]))),
+        }
+    }
+
+    contents.push_str("\n# Tech chest tools ([H] in the safehouse). Add as many as you like.\n");
+    let mut added_tools = false;
+    for (name, command) in INIT_CHEST_TOOLS {
+        if !program_on_path(command[0]) {
+            continue;
+        }
+        let answer = ask(format!(
+            "Add chest tool {name} ({})? [Y/n]",
+            render(command)
+        ))?;
+        if answer.is_some_and(|answer| answer.eq_ignore_ascii_case("n")) {
+            continue;
+        }
+        contents.push_str(&format!(
+            "\n[[actions.chest_tools]]\nname = {}\ncommand = {}\n",
+            toml::Value::from(*name),
+            render(command)
+        ));
+        if let Some(mode) = mode(command) {
+            contents.push_str(&format!("mode = \"{mode}\"\n"));
+        }
+        added_tools = true;
+    }
+    if !added_tools {
+        contents.push_str(
+            "\n# [[actions.chest_tools]]\n# name = \"Nmap Sweep\"\n# command = [\"nmap\", \"-sV\", \"10.0.0.0/24\"]\n",
+        );
+    }
+    Ok(contents)
+}
+
+fn ignored_key_path(path: &serde_ignored::Path) -> Vec<String> {
+    let mut segments = match path {
+        serde_ignored::Path::Root => return Vec::new(),
+        serde_ignored::Path::Seq { parent, .. }
+        | serde_ignored::Path::Map { parent, .. }
+        | serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => ignored_key_path(parent),
+    };
+    match path {
+        serde_ignored::Path::Seq { index, .. } => segments.push(index.to_string()),
+        serde_ignored::Path::Map { key, .. } => segments.push(key.clone()),
+        _ => {}
+    }
+    segments
+}
+
+/// Looks up a dotted key path, with array entries as indices, in a parsed table.
+fn lookup_key<'a>(table: &'a toml::Table, path: &str) -> Option<&'a toml::Value> {
+    let mut segments = path.split('.');
+    let mut value = table.get(segments.next()?)?;
+    for segment in segments {
+        value = match value {
+            toml::Value::Table(table) => table.get(segment)?,
+            toml::Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+fn display_key_path(path: &[String]) -> String {
+    let mut display = String::new();
+    for segment in path {
+        if segment.parse::<usize>().is_ok() {
+            display.push_str(&format!("[{segment}]"));
+        } else {
+            if !display.is_empty() {
+                display.push('.');
+            }
+            display.push_str(segment);
+        }
+    }
+    display
+}
+
+/// Byte offset of the deepest part of `path` present in the document.
+fn key_offset(document: &toml_edit::ImDocument<&str>, path: &[String]) -> usize {
+    let mut item = document.as_item();
+    let mut offset = 0;
+    for segment in path {
+        let (next, span) = match segment.parse::<usize>() {
+            Ok(index) if item.is_array() || item.is_array_of_tables() => {
+                let Some(next) = item.get(index) else {
+                    break;
+                };
+                (next, next.span())
+            }
+            _ => {
+                let Some((key, next)) = item
+                    .as_table_like()
+                    .and_then(|table| table.get_key_value(segment))
+                else {
+                    break;
+                };
+                (next, key.span())
+            }
+        };
+        if let Some(span) = span {
+            offset = span.start;
+        }
+        item = next;
+    }
+    offset
+}
+
+fn line_and_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map_or(0, |tail| tail.chars().count())
+        + 1;
+    (line, column)
+}
+
+fn program_on_path(program: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    let executable = |path: &Path| {
+        fs::metadata(path)
+            .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    };
+    if program.contains('/') {
+        return executable(Path::new(program));
+    }
+    env::var_os("PATH")
+        .is_some_and(|paths| env::split_paths(&paths).any(|dir| executable(&dir.join(program))))
+}
+
+fn read_line_trimmed() -> io::Result<Option<String>> {
+    read_prompted_line(false)
+}
+
+/// Prompts for and reads one line. A signal that interrupts the read is
+/// waited out, unless `hub_interrupts` is set and it was a SIGHUP asking for a
+/// config reload or a SIGCHLD from a finished tool; then the read ends with
+/// `Interrupted`.
+fn read_prompted_line(hub_interrupts: bool) -> io::Result<Option<String>> {
+    print!("{}>{} ", paint(COLOR_PROMPT), paint(RESET));
+    io::stdout().flush()?;
+    // `read_line` retries interrupted reads itself, so this reads by hand.
+    let mut stdin = io::stdin().lock();
+    let mut line = Vec::new();
+    loop {
+        let available = match stdin.fill_buf() {
+            Ok(available) => available,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {
+                let pending = CONFIG_RELOAD_REQUESTED.load(Ordering::SeqCst)
+                    || CHILD_EXITED.load(Ordering::SeqCst);
+                if hub_interrupts && pending {
+                    println!();
+                    return Err(err);
+                }
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        if available.is_empty() {
+            if line.is_empty() {
+                return Ok(None);
+            }
+            break;
+        }
+        let taken = available
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map_or(available.len(), |newline| newline + 1);
+        line.extend_from_slice(&available[..taken]);
+        stdin.consume(taken);
+        if line.ends_with(b"\n") {
+            break;
+        }
+    }
+    Ok(Some(String::from_utf8_lossy(&line).trim().to_string()))
+}
+
+fn read_choice() -> io::Result<Option<char>> {
+    Ok(first_choice(read_line_trimmed()?))
+}
+
+/// `read_choice` for the hub menu, which a config reload or a finished tool
+/// interrupts.
+fn read_menu_choice() -> io::Result<Option<char>> {
+    Ok(first_choice(read_prompted_line(true)?))
+}
+
+fn first_choice(input: Option<String>) -> Option<char> {
+    input?
+        .chars()
+        .find(|c| !c.is_whitespace())
+        .map(|ch| ch.to_ascii_lowercase())
+}
+
+/// What `stream_command` saw of a finished child.
+struct StreamedRun {
+    stdout: String,
+    /// Whether any line was printed, stderr included.
+    printed: bool,
+    status: ExitStatus,
+}
+
+/// Runs `command` to completion, printing its stdout and stderr line by line
+/// as they arrive (stderr in the error color), and returns its stdout and
+/// exit status.
+/// On a terminal a spinner with the elapsed time sits below the output. Ctrl-C
+/// or running past `timeout` stops the child's process group with SIGTERM,
+/// then SIGKILL after `TIMEOUT_GRACE`, and leaves the hub running.
+fn stream_command(command: &CommandSpec, timeout: Option<Duration>) -> io::Result<StreamedRun> {
+    let mut child = build_command(command)?
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // Its own group, so a stop reaches everything it started and the
+        // terminal's Ctrl-C only reaches the hub.
+        .process_group(0)
+        .spawn()?;
+    let (sender, receiver) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout, false, sender.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(stderr, true, sender);
+    }
+
+    STREAM_INTERRUPTED.store(false, Ordering::SeqCst);
+    let streamed = {
+        let _sigint = SignalGuard::install(libc::SIGINT, interrupt_stream);
+        pump_child_output(&mut child, &receiver, timeout)
+    };
+    if streamed.is_err() {
+        // Whatever cut the run short, nothing the child started outlives it.
+        signal_group(&child, libc::SIGKILL);
+    }
+    let status = child.wait();
+    let (stdout, printed) = streamed?;
+    Ok(StreamedRun {
+        stdout,
+        printed,
+        status: status?,
+    })
+}
+
+/// Prints the child's lines until it exits, then whatever its pipes still
+/// hold, and returns its stdout and whether anything was printed. Stops the
+/// child on Ctrl-C or past `timeout` and reports that as an error.
+fn pump_child_output(
+    child: &mut Child,
+    receiver: &mpsc::Receiver<(bool, String)>,
+    timeout: Option<Duration>,
+) -> io::Result<(String, bool)> {
+    // SAFETY: isatty only inspects the descriptor.
+    let interactive = unsafe { libc::isatty(libc::STDOUT_FILENO) } == 1;
+    let started = Instant::now();
+    let mut stdout = String::new();
+    let mut printed = false;
+    let mut show_line = |(from_stderr, line): (bool, String)| {
+        if interactive {
+            print!("\r\x1B[K");
+        }
+        if from_stderr {
+            println!("{}{line}{}", paint(COLOR_ERROR), paint(RESET));
+        } else {
+            println!("{line}");
+            stdout.push_str(&line);
+            stdout.push('\n');
+        }
+        printed = true;
+    };
+    let mut cancelled = false;
+    let mut timed_out = false;
+    let mut terminated_at: Option<Instant> = None;
+    let mut killed = false;
+    while child.try_wait()?.is_none() {
+        match receiver.recv_timeout(STREAM_TICK) {
+            Ok(line) => show_line(line),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            // Both pipes closed but the child runs on; keep polling it.
+            Err(mpsc::RecvTimeoutError::Disconnected) => thread::sleep(STREAM_TICK),
+        }
+        let elapsed = started.elapsed();
+        match terminated_at {
+            None => {
+                cancelled = STREAM_INTERRUPTED.load(Ordering::SeqCst);
+                timed_out = timeout.is_some_and(|limit| elapsed >= limit);
+                if cancelled || timed_out {
+                    signal_group(child, libc::SIGTERM);
+                    terminated_at = Some(Instant::now());
+                }
+            }
+            Some(at) => {
+                if !killed && at.elapsed() >= TIMEOUT_GRACE {
+                    signal_group(child, libc::SIGKILL);
+                    killed = true;
+                }
+            }
+        }
+        if interactive {
+            let frame = SPINNER_FRAMES[(elapsed.as_millis() / 100) as usize % SPINNER_FRAMES.len()];
+            let limit = timeout
+                .map(|limit| format!(" of {}", format_elapsed(limit.as_secs())))
+                .unwrap_or_default();
+            print!(
+                "\r\x1B[K{}{frame} running {}{limit} (Ctrl-C cancels){}",
+                paint(COLOR_PROMPT),
+                format_elapsed(elapsed.as_secs()),
+                paint(RESET)
+            );
+        }
+        io::stdout().flush()?;
+    }
+    // The readers may still hold the last lines. A daemon the child left
+    // holding its pipes keeps them open, so only wait so long for the rest.
+    let deadline = Instant::now() + STREAM_DRAIN;
+    while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+        match receiver.recv_timeout(left) {
+            Ok(line) => show_line(line),
+            Err(_) => break,
+        }
+    }
+    if interactive {
+        print!("\r\x1B[K");
+    }
+    io::stdout().flush()?;
+    if cancelled {
+        return Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "cancelled with Ctrl-C",
+        ));
+    }
+    if let Some(limit) = timeout.filter(|_| timed_out) {
+        let signal = if killed { "SIGKILL" } else { "SIGTERM" };
+        return Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!(
+                "timed out after {} and was stopped with {signal}",
+                format_elapsed(limit.as_secs())
+            ),
+        ));
+    }
+    Ok((stdout, printed))
+}
+
+/// Sends `signal` to the process group `stream_command` started `child` in.
+fn signal_group(child: &Child, signal: libc::c_int) {
+    // SAFETY: kill has no memory-safety preconditions. The child is unreaped,
+    // or its group still has members, so the group ID cannot have been reused.
+    unsafe { libc::kill(-(child.id() as libc::pid_t), signal) };
+}
+
+/// Puts a signal's previous action back when dropped, so every way out of the
+/// scope restores it.
+struct SignalGuard {
+    signal: libc::c_int,
+    previous: libc::sigaction,
+}
+
+impl SignalGuard {
+    fn install(signal: libc::c_int, handler: extern "C" fn(libc::c_int)) -> Self {
+        SignalGuard {
+            signal,
+            previous: install_signal_handler(signal, handler),
+        }
+    }
+
+    fn ignore(signal: libc::c_int) -> Self {
+        SignalGuard {
+            signal,
+            previous: set_signal_disposition(signal, libc::SIG_IGN),
+        }
+    }
+}
+
+impl Drop for SignalGuard {
+    fn drop(&mut self) {
+        // SAFETY: `previous` came from sigaction for this same signal.
+        unsafe { libc::sigaction(self.signal, &self.previous, std::ptr::null_mut()) };
+    }
+}
+
+/// Installs `handler` for `signal` without `SA_RESTART`, so a blocking read
+/// returns `Interrupted` when it fires, and returns the previous action.
+fn install_signal_handler(
+    signal: libc::c_int,
+    handler: extern "C" fn(libc::c_int),
+) -> libc::sigaction {
+    set_signal_disposition(signal, handler as libc::sighandler_t)
+}
+
+/// Sets `signal` to `disposition` (a handler, `SIG_IGN`, or `SIG_DFL`) without
+/// `SA_RESTART` and returns the previous action.
+fn set_signal_disposition(signal: libc::c_int, disposition: libc::sighandler_t) -> libc::sigaction {
+    // SAFETY: both structs are plain data that sigaction fills in or reads, and
+    // the handlers passed here only store to atomics.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        let mut previous: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = disposition;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(signal, &action, &mut previous);
+        previous
+    }
+}
+
+/// Sends each line read from `pipe` to `sender`, tagged with whether it came
+/// from stderr. The thread ends when the pipe closes or the receiver is gone.
+fn forward_lines(
+    pipe: impl Read + Send + 'static,
+    from_stderr: bool,
+    sender: mpsc::Sender<(bool, String)>,
+) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(pipe);
+        let mut line = Vec::new();
+        while reader
+            .read_until(b'\n', &mut line)
+            .is_ok_and(|read| read > 0)
+        {
+            let text = String::from_utf8_lossy(&line)
+                .trim_end_matches(['\n', '\r'])
+                .to_string();
+            if sender.send((from_stderr, text)).is_err() {
+                break;
+            }
+            line.clear();
+        }
+    });
+}
+
+/// Launches `command` in its configured mode, reporting a failure under
+/// `failure_context`.
+fn launch_command(command: &CommandSpec, failure_context: &str) -> ActionOutcome {
+    let waits = command.mode == LaunchMode::Background && WAIT_FOR_COMMANDS.load(Ordering::SeqCst);
+    if command.mode == LaunchMode::Foreground || waits {
+        return match run_in_foreground(command) {
+            Ok(status) => exit_outcome(command, status),
+            Err(err) => {
+                eprintln!("{failure_context}: {err}");
+                ActionOutcome::Failed(err.to_string())
+            }
+        };
+    }
+    match spawn_command(command) {
+        Ok((pid, log)) => {
+            if let Some(log) = log {
+                println!(
+                    "Running in the background; output goes to {}.",
+                    log.display()
+                );
+            }
+            match settle_child(pid) {
+                Some(status) => exit_outcome(command, status),
+                None => ActionOutcome::Spawned(pid),
+            }
+        }
+        Err(err) => {
+            eprintln!("{failure_context}: {err}");
+            ActionOutcome::Failed(err.to_string())
+        }
     }
+}
 
-    fn explore_world_command(&self) -> Option<&[String]> {
-        self.explore_world.as_deref().filter(|cmd| !cmd.is_empty())
+/// Hands the terminal to `command` until it exits. The hub ignores SIGINT and
+/// SIGQUIT meanwhile so Ctrl-C only reaches the child, and afterwards restores
+/// the terminal settings and cursor the child may have left changed.
+fn run_in_foreground(command: &CommandSpec) -> io::Result<ExitStatus> {
+    let mut process = build_command(command)?;
+    // SAFETY: `termios` is plain old data that tcgetattr fills in.
+    let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+    let saved = unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } == 0;
+    let mut child = process
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+    // Ignored only once the child is spawned, since it would inherit SIG_IGN.
+    let status = {
+        let _sigint = SignalGuard::ignore(libc::SIGINT);
+        let _sigquit = SignalGuard::ignore(libc::SIGQUIT);
+        child.wait()
+    };
+    if saved {
+        // SAFETY: `termios` was filled in by tcgetattr above.
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) };
     }
-
-    fn grin_wallet_command(&self) -> Option<&[String]> {
-        self.grin_wallet.as_deref().filter(|cmd| !cmd.is_empty())
+    if saved {
+        print!("\x1B[?25h");
+        io::stdout().flush()?;
     }
+    status
 }
 
-#[derive(Default, Deserialize)]
-struct CharacterConfig {
-    #[serde(default)]
-    clothing: Vec<String>,
+/// Starts a background or detached `command` alongside the hub and hands it
+/// to the supervisor. Returns its PID and, for a background child, the log file.
+fn spawn_command(command: &CommandSpec) -> io::Result<(u32, Option<PathBuf>)> {
+    let mut process = build_command(command)?;
+    process.stdin(Stdio::null());
+    let log = if command.mode == LaunchMode::Detached {
+        process.stdout(Stdio::null()).stderr(Stdio::null());
+        // SAFETY: setsid is async-signal-safe and the closure does not allocate.
+        unsafe {
+            process.pre_exec(|| {
+                if libc::setsid() == -1 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(())
+                }
+            });
+        }
+        None
+    } else {
+        let path = command_log_path(command);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        process.stdout(file.try_clone()?).stderr(file);
+        Some(path)
+    };
+    let child = process.spawn()?;
+    let pid = child.id();
+    CHILDREN
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(SupervisedChild {
+            child,
+            label: command.argv().join(" "),
+            started_at: unix_timestamp(),
+            log: log.clone(),
+            ok_exit_codes: command.ok_exit_codes.clone(),
+            action: None,
+        });
+    Ok((pid, log))
 }
 
-#[derive(Clone, Default, Deserialize)]
-struct NamedCommand {
-    name: String,
-    #[serde(default)]
-    command: Vec<String>,
+/// Gives a child `spawn_command` just started `SPAWN_SETTLE` to exit, so a
+/// tool that fails at once (a bad flag, a missing file) counts as failed
+/// rather than launched. Returns its status, and forgets it, if it exited.
+fn settle_child(pid: u32) -> Option<ExitStatus> {
+    let deadline = Instant::now() + SPAWN_SETTLE;
+    loop {
+        {
+            let mut children = CHILDREN.lock().unwrap_or_else(PoisonError::into_inner);
+            let index = children.iter().position(|entry| entry.child.id() == pid)?;
+            if let Ok(Some(status)) = children[index].child.try_wait() {
+                children.remove(index);
+                return Some(status);
+            }
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
 }
 
-struct Character {
-    name: String,
-    level: u32,
-    xp: u32,
-    clothing: Vec<String>,
-    pockets: Vec<PocketItem>,
+/// `<data dir>/logs/<program>-<unix time>.log`, e.g. `logs/nmap-1714590843.log`.
+fn command_log_path(command: &CommandSpec) -> PathBuf {
+    let program = if command.shell {
+        String::from("sh")
+    } else {
+        Path::new(&command.args[0])
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| String::from("command"))
+    };
+    data_dir()
+        .unwrap_or_else(|| env::temp_dir().join("lord"))
+        .join("logs")
+        .join(format!("{program}-{}.log", unix_timestamp()))
 }
 
-impl Character {
-    fn new(config: &Config) -> Self {
-        let clothing = if config.character.clothing.is_empty() {
-            vec![
-                String::from("Aurora-weave jacket"),
-                String::from("Carbon-thread boots"),
-                String::from("Holographic lapel pin"),
-            ]
-        } else {
-            config.character.clothing.clone()
-        };
+/// Collects the exit status of every finished child so none linger as zombies,
+/// returning the ones that exited since the last call.
+fn reap_children() -> Vec<(SupervisedChild, ExitStatus)> {
+    CHILDREN
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .extract_if(.., |entry| !matches!(entry.child.try_wait(), Ok(None)))
+        .filter_map(|mut entry| {
+            // The child was just reaped, so this returns the cached status.
+            let status = entry.child.try_wait().ok().flatten()?;
+            Some((entry, status))
+        })
+        .collect()
+}
 
-        let mut pockets = Vec::new();
-        pockets.push(PocketItem::grin_wallet(
-            config.actions.grin_wallet_command(),
-        ));
+/// Records which action launched the supervised child `pid`.
+fn tag_child(pid: u32, action_key: String) {
+    if let Some(entry) = CHILDREN
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter_mut()
+        .find(|entry| entry.child.id() == pid)
+    {
+        entry.action = Some(action_key);
+    }
+}
 
-        Self {
-            name: determine_character_name(),
-            level: 1,
-            xp: 0,
-            clothing,
-            pockets,
+/// Whether `status` is one of `ok_exit_codes`, or zero when none are listed.
+/// Death by a signal never counts as success.
+fn exit_accepted(status: ExitStatus, ok_exit_codes: &[i32]) -> bool {
+    status.code().is_some_and(|code| {
+        if ok_exit_codes.is_empty() {
+            code == 0
+        } else {
+            ok_exit_codes.contains(&code)
         }
+    })
+}
+
+/// Describes how a child ended, e.g. `exited with status 1` or
+/// `was killed by signal 9 (Killed)`.
+fn describe_exit(status: ExitStatus) -> String {
+    if let Some(code) = status.code() {
+        return format!("exited with status {code}");
+    }
+    let Some(signal) = status.signal() else {
+        return String::from("exited");
+    };
+    // SAFETY: strsignal returns a NUL-terminated string owned by libc.
+    let name = unsafe { libc::strsignal(signal) };
+    if name.is_null() {
+        return format!("was killed by signal {signal}");
     }
+    // SAFETY: checked non-null above, and nothing else calls strsignal before
+    // the string is copied out.
+    let name = unsafe { std::ffi::CStr::from_ptr(name) }.to_string_lossy();
+    format!("was killed by signal {signal} ({name})")
+}
 
-    fn render_sheet(&self) {
-        println!("\n{}== Operator Dossier =={}", COLOR_TITLE, RESET);
-        println!("{}Name:{} {}", COLOR_OPTION_TEXT, RESET, self.name);
+/// Prints how a finished `command` exited, in the failure color unless its
+/// status is accepted, and turns that into an outcome.
+fn exit_outcome(command: &CommandSpec, status: ExitStatus) -> ActionOutcome {
+    let description = describe_exit(status);
+    if command.accepts(status) {
         println!(
-            "{}Level:{} {}    {}XP:{} {}/{}",
-            COLOR_OPTION_TEXT,
-            RESET,
-            self.level,
-            COLOR_OPTION_TEXT,
-            RESET,
-            self.xp,
-            self.experience_to_next_level()
+            "{}The command {description}.{}",
+            paint(COLOR_PROMPT),
+            paint(RESET)
         );
-        println!("{}Clothing:{}", COLOR_OPTION_TEXT, RESET);
-        for item in &self.clothing {
-            println!("  - {}", item);
-        }
-        println!("{}Pockets:{}", COLOR_OPTION_TEXT, RESET);
-        if self.pockets.is_empty() {
-            println!("  (empty)");
-        } else {
-            for (index, pocket) in self.pockets.iter().enumerate() {
-                println!("  [{}] {} — {}", index + 1, pocket.name, pocket.description);
-            }
-        }
+        ActionOutcome::Completed
+    } else {
+        println!(
+            "{}The command {description}.{}",
+            paint(COLOR_ERROR),
+            paint(RESET)
+        );
+        ActionOutcome::Failed(description)
     }
+}
 
-    fn gain_xp(&mut self, amount: u32) -> Option<u32> {
-        self.xp += amount;
-        let mut leveled = None;
-        loop {
-            let threshold = self.experience_to_next_level();
-            if self.xp < threshold {
-                break;
-            }
-            self.xp -= threshold;
-            self.level += 1;
-            leveled = Some(self.level);
-        }
-        leveled
+/// Sends `signal` to a supervised child. Only PIDs still in `CHILDREN` are
+/// signalled, so a recycled PID never reaches an unrelated process.
+fn signal_child(pid: u32, signal: libc::c_int) -> io::Result<()> {
+    let children = CHILDREN.lock().unwrap_or_else(PoisonError::into_inner);
+    if !children.iter().any(|entry| entry.child.id() == pid) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "the process already exited",
+        ));
     }
-
-    fn experience_to_next_level(&self) -> u32 {
-        25 + (self.level.saturating_sub(1) * 10)
+    // SAFETY: kill has no memory-safety preconditions; the child is unreaped,
+    // so its PID cannot have been reused.
+    if unsafe { libc::kill(pid as libc::pid_t, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
     }
 }
 
-struct PocketItem {
-    name: String,
-    description: String,
-    command: Option<Vec<String>>,
+/// Formats a duration as `42s`, `5m02s`, or `1h07m`.
+fn format_elapsed(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
 }
 
-impl PocketItem {
-    fn grin_wallet(command: Option<&[String]>) -> Self {
-        let default_command = vec![String::from("grin-wallet")];
-        Self {
-            name: String::from("Grin Wallet"),
-            description: String::from("Shielded grin-wallet client"),
-            command: Some(command.unwrap_or(&default_command).to_vec()),
-        }
+/// Prepares a configured command: arguments, `cwd`, and `env` values are
+/// expanded with `expand_command_arg`, and the hub variables are exported to
+/// the child. A shell script is left for `sh` to expand itself.
+fn build_command(command: &CommandSpec) -> io::Result<Command> {
+    if command.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty command"));
+    }
+    let vars = COMMAND_VARS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    let expand = |value: &String| expand_command_arg(value, &vars);
+    let mut process = if command.shell {
+        let mut process = Command::new("sh");
+        process
+            .arg("-c")
+            .arg(&command.args[0])
+            .args(command.args[1..].iter().map(expand));
+        process
+    } else {
+        let mut process = Command::new(expand(&command.args[0]));
+        process.args(command.args[1..].iter().map(expand));
+        process
+    };
+    if command.clear_env {
+        process.env_clear();
+    }
+    process.envs(&vars);
+    for (name, value) in &command.env {
+        process.env(name, expand(value));
+    }
+    if let Some(cwd) = &command.cwd {
+        process.current_dir(expand(cwd));
     }
+    Ok(process)
+}
 
-    fn use_item(&self) -> io::Result<()> {
-        match &self.command {
-            Some(cmd) => {
-                if let Err(err) = spawn_command(cmd) {
-                    eprintln!("{} refuses to activate: {err}", self.name);
-                }
-                Ok(())
+/// Expands a leading `~`, `$NAME`, `${NAME}`, and `${NAME:-default}` the way a
+/// shell would, checking the hub variables before the environment. Unset
+/// variables expand to nothing, and `$$` is a literal `$`.
+fn expand_command_arg(arg: &str, vars: &BTreeMap<String, String>) -> String {
+    let lookup = |name: &str| {
+        vars.get(name)
+            .cloned()
+            .or_else(|| env::var(name).ok())
+            .filter(|value| !value.is_empty())
+    };
+    let mut expanded = String::new();
+    let mut rest = arg;
+    if let Some(tail) = arg.strip_prefix('~')
+        && (tail.is_empty() || tail.starts_with('/'))
+        && let Some(home) = home_dir()
+    {
+        expanded.push_str(&home.display().to_string());
+        rest = tail;
+    }
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        if let Some(tail) = after.strip_prefix('$') {
+            expanded.push('$');
+            rest = tail;
+        } else if let Some(braced) = after.strip_prefix('{')
+            && let Some(end) = braced.find('}')
+        {
+            let inner = &braced[..end];
+            let (name, default) = match inner.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (inner, None),
+            };
+            match lookup(name) {
+                Some(value) => expanded.push_str(&value),
+                None => expanded.push_str(&expand_command_arg(default.unwrap_or(""), vars)),
             }
-            None => {
-                println!("This pocket item is ornamental only.");
-                Ok(())
+            rest = &braced[end + 1..];
+        } else {
+            let name_len = after
+                .char_indices()
+                .find(|(index, ch)| {
+                    !(ch.is_ascii_alphanumeric() || *ch == '_')
+                        || (*index == 0 && ch.is_ascii_digit())
+                })
+                .map_or(after.len(), |(index, _)| index);
+            if name_len == 0 {
+                expanded.push('$');
+            } else {
+                expanded.push_str(&lookup(&after[..name_len]).unwrap_or_default());
             }
+            rest = &after[name_len..];
         }
     }
+    expanded.push_str(rest);
+    expanded
 }
 
-impl NamedCommand {
-    fn is_valid(&self) -> bool {
-        !self.name.trim().is_empty() && !self.command.is_empty()
+/// Config layers from lowest to highest precedence: system-wide, per-user,
+/// the per-user `conf.d` drop-ins in lexical order, then the project file in
+/// the working directory.
+fn config_paths() -> Vec<PathBuf> {
+    if let Some(path) = CONFIG_OVERRIDE.get() {
+        return vec![path.clone()];
     }
+    let mut paths = vec![PathBuf::from("/etc/lord/config.toml")];
+    if let Some(dir) = config_dir() {
+        paths.push(dir.join("config.toml"));
+        paths.extend(config_drop_ins(&dir.join("conf.d")));
+    }
+    paths.push(PathBuf::from("lord_config.toml"));
+    paths
+}
 
-    fn command(&self) -> Option<&[String]> {
-        if self.command.is_empty() {
-            None
-        } else {
-            Some(&self.command)
+/// Each config layer with its modification time, to spot edits (and new or
+/// removed drop-ins) while the hub runs.
+fn config_stamp() -> Vec<(PathBuf, Option<SystemTime>)> {
+    config_paths()
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+            (path, modified)
+        })
+        .collect()
+}
+
+/// Checks `config_stamp` every `CONFIG_POLL` on a background thread and sends
+/// the calling thread a SIGHUP when it changes, so an edit also reloads a hub
+/// menu that is waiting for input.
+fn watch_config_files() {
+    // SAFETY: pthread_self has no preconditions.
+    let hub = unsafe { libc::pthread_self() };
+    let mut stamp = config_stamp();
+    thread::spawn(move || {
+        loop {
+            thread::sleep(CONFIG_POLL);
+            let current = config_stamp();
+            if current != stamp {
+                stamp = current;
+                // SAFETY: the hub runs on the main thread, which outlives this
+                // one, and SIGHUP has a handler, so it cannot end the process.
+                unsafe { libc::pthread_kill(hub, libc::SIGHUP) };
+            }
         }
-    }
+    });
 }
 
-fn show_location(location: Location, title: &str) {
-    println!();
-    print_centered_colored(title, COLOR_TITLE);
-    for line in location_art(location).lines() {
-        print_centered_colored(line, COLOR_ART);
-    }
+fn config_drop_ins(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut drop_ins: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml") && path.is_file())
+        .collect();
+    drop_ins.sort();
+    drop_ins
 }
 
-fn location_art(location: Location) -> &'static str {
-    match location {
-        Location::TownSquare => TOWN_SQUARE_ART,
-        Location::Graveyard => GRAVEYARD_ART,
-        Location::Room => ROOM_ART,
+fn config_dir() -> Option<PathBuf> {
+    if let Some(xdg) = env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(xdg).join("lord"));
     }
+    home_dir().map(|home| home.join(".config/lord"))
 }
 
-fn read_line_trimmed() -> io::Result<Option<String>> {
-    print!("{}>{} ", COLOR_PROMPT, RESET);
-    io::stdout().flush()?;
-    let mut buffer = String::new();
-    let bytes = io::stdin().read_line(&mut buffer)?;
-    if bytes == 0 {
-        return Ok(None);
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from)
+}
+
+fn data_dir() -> Option<PathBuf> {
+    if let Some(xdg) = env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(xdg).join("lord"));
     }
-    Ok(Some(buffer.trim().to_string()))
+    home_dir().map(|home| home.join(".local/share/lord"))
 }
 
-fn read_choice() -> io::Result<Option<char>> {
-    match read_line_trimmed()? {
-        None => Ok(None),
-        Some(input) => {
-            if let Some(ch) = input.chars().find(|c| !c.is_whitespace()) {
-                Ok(Some(ch.to_ascii_lowercase()))
+fn profiles_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("profiles"))
+}
+
+/// Maps an operator name to its save file, e.g. "Night Owl" -> `night-owl.toml`.
+fn profile_path(name: &str) -> Option<PathBuf> {
+    let slug = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c.to_ascii_lowercase()
             } else {
-                Ok(None)
+                '-'
             }
-        }
+        })
+        .collect::<String>();
+    let slug = slug.trim_matches('-');
+    let slug = if slug.is_empty() { "operator" } else { slug };
+    profiles_dir().map(|dir| dir.join(format!("{slug}.toml")))
+}
+
+struct ProfileEntry {
+    file_stem: String,
+    path: PathBuf,
+    save: io::Result<SaveData>,
+}
+
+fn list_profiles() -> Vec<ProfileEntry> {
+    let Some(entries) = profiles_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut profiles: Vec<ProfileEntry> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .map(|path| {
+            let file_stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let save = match SaveData::load(&path) {
+                Ok(Some(save)) => Ok(save),
+                Ok(None) => Err(io::Error::new(io::ErrorKind::NotFound, "save disappeared")),
+                Err(err) => Err(err),
+            };
+            ProfileEntry {
+                file_stem,
+                path,
+                save,
+            }
+        })
+        .collect();
+    profiles.sort_by(|a, b| a.file_stem.cmp(&b.file_stem));
+    profiles
+}
+
+fn prompt_delete_profile(profiles: &[ProfileEntry]) -> io::Result<()> {
+    print_centered_colored("Delete which profile number?", COLOR_PROMPT);
+    let Some(input) = read_line_trimmed()? else {
+        return Ok(());
+    };
+    let Some(profile) = input
+        .parse::<usize>()
+        .ok()
+        .filter(|choice| *choice >= 1 && *choice <= profiles.len())
+        .map(|choice| &profiles[choice - 1])
+    else {
+        return Ok(());
+    };
+    print_centered_colored(
+        &format!("Erase {} for good? (y/N)", profile.file_stem),
+        COLOR_PROMPT,
+    );
+    if read_choice()? == Some('y')
+        && let Err(err) = fs::remove_file(&profile.path)
+    {
+        eprintln!("Failed to delete {}: {err}", profile.path.display());
+        wait_for_continue()?;
     }
+    Ok(())
 }
 
-fn run_command_and_capture(command: &[String]) -> io::Result<String> {
-    if command.is_empty() {
-        return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty command"));
+/// Moves a pre-profile `save.toml` into the profiles directory so earlier
+/// progress shows up in the picker.
+fn migrate_legacy_save() {
+    let (Some(legacy), Some(dir)) = (data_dir().map(|dir| dir.join("save.toml")), profiles_dir())
+    else {
+        return;
+    };
+    if !legacy.exists() || fs::read_dir(&dir).is_ok_and(|mut entries| entries.next().is_some()) {
+        return;
     }
-    let mut process = Command::new(&command[0]);
-    if command.len() > 1 {
-        process.args(&command[1..]);
+    let Ok(Some(mut save)) = SaveData::load(&legacy) else {
+        return;
+    };
+    if save.name.trim().is_empty() {
+        save.name = determine_character_name();
     }
-    let output = process.output()?;
-    let mut combined = String::new();
-    combined.push_str(&String::from_utf8_lossy(&output.stdout));
-    if !output.stderr.is_empty() {
-        combined.push_str(&String::from_utf8_lossy(&output.stderr));
+    let Some(path) = profile_path(&save.name) else {
+        return;
+    };
+    match save.write(&path) {
+        Ok(()) => {
+            let _ = fs::remove_file(&legacy);
+        }
+        Err(err) => eprintln!("Failed to migrate {}: {err}", legacy.display()),
     }
-    Ok(combined)
 }
 
-fn spawn_command(command: &[String]) -> io::Result<()> {
-    if command.is_empty() {
-        return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty command"));
+/// Formats `secs` as a local RFC 3339 timestamp, e.g. `2024-05-01T21:14:03+02:00`.
+fn local_timestamp(secs: u64) -> String {
+    let time = secs as libc::time_t;
+    // SAFETY: `tm` is plain old data and localtime_r only writes into it.
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        tm = unsafe { std::mem::zeroed() };
+        unsafe { libc::gmtime_r(&time, &mut tm) };
     }
-    let mut process = Command::new(&command[0]);
-    if command.len() > 1 {
-        process.args(&command[1..]);
+    let offset = tm.tm_gmtoff;
+    let sign = if offset < 0 { '-' } else { '+' };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec,
+        sign,
+        offset.abs() / 3600,
+        offset.abs() % 3600 / 60
+    )
+}
+
+/// Days since the Unix epoch for a `YYYY-MM-DD` date, used to compare calendar days.
+fn day_number(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    // Howard Hinnant's days_from_civil.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+/// The streak after the first visit on `today`: one longer when `last_day` was
+/// the day before, otherwise a fresh streak of one.
+fn next_streak(last_day: &str, today: &str, streak: u32) -> u32 {
+    match (day_number(last_day), day_number(today)) {
+        (Some(last), Some(now)) if now - last == 1 => streak + 1,
+        _ => 1,
     }
-    process
-        .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .spawn()
-        .map(|_| ())
 }
 
-fn config_paths() -> Vec<PathBuf> {
-    let mut paths = Vec::new();
-    paths.push(PathBuf::from("lord_config.toml"));
-    if let Some(mut home) = home_dir() {
-        home.push(".config/lord/config.toml");
-        paths.push(home);
+/// The key for `action` in the per-action save maps, e.g. `chest:Nmap Sweep`.
+fn action_key(action: &str, target: Option<&str>) -> String {
+    match target {
+        Some(target) => format!("{action}:{target}"),
+        None => action.to_string(),
     }
-    paths
 }
 
-fn home_dir() -> Option<PathBuf> {
-    std::env::var_os("HOME").map(PathBuf::from)
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// Writes to a sibling temp file and renames it over `path`, so readers only
+/// ever see the old contents or the complete new ones.
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(format!(".tmp-{}", std::process::id()));
+    let tmp_path = PathBuf::from(tmp_name);
+    let result = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
 }
 
 fn tomb_search_paths() -> Vec<PathBuf> {
@@ -859,14 +5349,14 @@ fn wait_for_continue() -> io::Result<()> {
 fn print_option(key: &str, description: &str) {
     println!(
         "{}[{}{}{}]{} {}{}{}",
-        COLOR_OPTION_TEXT,
-        COLOR_OPTION_KEY,
+        paint(COLOR_OPTION_TEXT),
+        paint(COLOR_OPTION_KEY),
         key,
-        COLOR_OPTION_TEXT,
-        RESET,
-        COLOR_OPTION_TEXT,
+        paint(COLOR_OPTION_TEXT),
+        paint(RESET),
+        paint(COLOR_OPTION_TEXT),
         description,
-        RESET
+        paint(RESET)
     );
 }
 
@@ -930,11 +5420,406 @@ fn padding_for_text(text: &str) -> String {
     " ".repeat(padding)
 }
 
+fn paint(code: &str) -> &str {
+    if COLOR_ENABLED.load(Ordering::Relaxed) {
+        code
+    } else {
+        ""
+    }
+}
+
 fn print_centered_colored(text: &str, color: &str) {
     if text.trim().is_empty() {
         println!();
         return;
     }
     let padding = padding_for_text(text);
-    println!("{}{}{}{}", padding, color, text, RESET);
+    println!("{}{}{}{}", padding, paint(color), text, paint(RESET));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merged_issues(source: &str) -> Vec<String> {
+        let table: toml::Table = source.parse().unwrap();
+        let config: Config = toml::Value::Table(table.clone()).try_into().unwrap();
+        check_merged_config(&config, &table)
+            .into_iter()
+            .map(|issue| issue.message)
+            .collect()
+    }
+
+    #[test]
+    fn empty_plain_command_is_reported() {
+        assert_eq!(
+            merged_issues("[actions]\ncheck_mail = []\n"),
+            ["empty command is ignored"]
+        );
+        assert_eq!(
+            merged_issues("[actions]\ncheck_mail = { command = [] }\n"),
+            ["empty command is ignored"]
+        );
+    }
+
+    #[test]
+    fn exit_to_unknown_location_is_reported() {
+        let source = "[[locations]]\nid = \"lab\"\nexits = [{ key = \"N\", label = \"Out\", to = \"nowhere\" }]\n";
+        assert_eq!(
+            merged_issues(source),
+            ["exit \"Out\" in lab leads to unknown location \"nowhere\""]
+        );
+    }
+
+    #[test]
+    fn profile_variables_skip_the_path_check() {
+        let source = "[actions]\ncheck_mail = [\"${LORD_PROFILE}/bin/x\"]\n";
+        assert!(merged_issues(source).is_empty());
+    }
+
+    #[test]
+    fn achievement_typos_are_reported() {
+        let source = "[[achievements]]\nid = \"m\"\ntitle = \"M\"\ntrigger = \"action\"\naction = \"check_mail\"\ncuont = 3\n";
+        let messages: Vec<String> = check_config_source(source)
+            .into_iter()
+            .map(|(_, message)| message)
+            .collect();
+        assert_eq!(messages, ["unknown key `achievements[0].cuont`"]);
+    }
+
+    #[test]
+    fn table_without_command_is_not_reported() {
+        let source = "[[character.pockets]]\nname = \"Field Notes\"\n";
+        assert!(merged_issues(source).is_empty());
+    }
+
+    fn expand(arg: &str) -> String {
+        let vars = BTreeMap::from([
+            ("LORD_TEST_NAME".to_string(), "knight".to_string()),
+            ("LORD_TEST_EMPTY".to_string(), String::new()),
+        ]);
+        expand_command_arg(arg, &vars)
+    }
+
+    #[test]
+    fn leading_tilde_expands_to_home() {
+        let home = home_dir().unwrap().display().to_string();
+        assert_eq!(expand("~"), home);
+        assert_eq!(expand("~/notes"), format!("{home}/notes"));
+        assert_eq!(expand("~knight/notes"), "~knight/notes");
+        assert_eq!(expand("a/~"), "a/~");
+    }
+
+    #[test]
+    fn variables_expand_from_hub_vars() {
+        assert_eq!(expand("$LORD_TEST_NAME.txt"), "knight.txt");
+        assert_eq!(expand("${LORD_TEST_NAME}s"), "knights");
+        assert_eq!(expand("a${LORD_TEST_UNSET}b"), "ab");
+        assert_eq!(expand("$$LORD_TEST_NAME"), "$LORD_TEST_NAME");
+        assert_eq!(expand("cost: $$5"), "cost: $5");
+    }
+
+    #[test]
+    fn defaults_apply_to_unset_and_empty_variables() {
+        assert_eq!(expand("${LORD_TEST_UNSET:-plain}"), "plain");
+        assert_eq!(expand("${LORD_TEST_EMPTY:-plain}"), "plain");
+        assert_eq!(expand("${LORD_TEST_NAME:-plain}"), "knight");
+        assert_eq!(expand("${LORD_TEST_UNSET:-$LORD_TEST_NAME}"), "knight");
+    }
+
+    #[test]
+    fn malformed_references_stay_literal() {
+        assert_eq!(expand("${LORD_TEST_NAME"), "${LORD_TEST_NAME");
+        assert_eq!(expand("$1"), "$1");
+        assert_eq!(expand("trailing $"), "trailing $");
+        assert_eq!(expand("$-x"), "$-x");
+    }
+
+    fn merge_layers(layers: &[(&str, &str)]) -> (toml::Table, BTreeMap<String, PathBuf>) {
+        let mut merged = toml::Table::new();
+        let mut origins = BTreeMap::new();
+        for (path, source) in layers {
+            let layer: toml::Table = source.parse().unwrap();
+            merge_config_table(&mut merged, layer, "", Path::new(path), &mut origins);
+        }
+        (merged, origins)
+    }
+
+    #[test]
+    fn later_layers_win_key_by_key() {
+        let (merged, origins) = merge_layers(&[
+            (
+                "system.toml",
+                "[character]\nname = \"Ada\"\nclass = \"Knight\"\n",
+            ),
+            ("user.toml", "[character]\nclass = \"Mage\"\n"),
+        ]);
+        let character = merged["character"].as_table().unwrap();
+        assert_eq!(character["name"].as_str(), Some("Ada"));
+        assert_eq!(character["class"].as_str(), Some("Mage"));
+        assert_eq!(origins["character.name"], Path::new("system.toml"));
+        assert_eq!(origins["character.class"], Path::new("user.toml"));
+    }
+
+    #[test]
+    fn appended_lists_replace_entries_with_the_same_name() {
+        let (merged, origins) = merge_layers(&[
+            (
+                "system.toml",
+                "[[locations]]\nid = \"lab\"\nname = \"Lab\"\n[[locations]]\nid = \"den\"\n",
+            ),
+            (
+                "user.toml",
+                "[[locations]]\nid = \"lab\"\n[[locations]]\nid = \"attic\"\n",
+            ),
+        ]);
+        let ids: Vec<&str> = merged["locations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, ["lab", "den", "attic"]);
+        assert!(merged["locations"][0].get("name").is_none());
+        assert!(!origins.contains_key("locations[0].name"));
+        assert_eq!(origins["locations[0].id"], Path::new("user.toml"));
+        assert_eq!(origins["locations[1].id"], Path::new("system.toml"));
+    }
+
+    #[test]
+    fn other_lists_are_replaced() {
+        let (merged, _) = merge_layers(&[
+            ("system.toml", "[actions]\ncheck_mail = [\"mutt\"]\n"),
+            ("user.toml", "[actions]\ncheck_mail = [\"aerc\", \"-a\"]\n"),
+        ]);
+        let command = merged["actions"]["check_mail"].as_array().unwrap();
+        assert_eq!(command.len(), 2);
+        assert_eq!(command[0].as_str(), Some("aerc"));
+    }
+
+    #[test]
+    fn entries_match_on_name_or_id() {
+        let entry = |source: &str| toml::Value::Table(source.parse().unwrap());
+        assert!(same_config_entry(
+            &entry("name = \"a\""),
+            &entry("name = \"a\"")
+        ));
+        assert!(same_config_entry(
+            &entry("id = \"a\""),
+            &entry("id = \"a\"\nname = \"b\"")
+        ));
+        assert!(!same_config_entry(
+            &entry("name = \"a\""),
+            &entry("name = \"b\"")
+        ));
+        assert!(!same_config_entry(
+            &entry("name = \"a\""),
+            &entry("id = \"a\"")
+        ));
+        assert!(!same_config_entry(
+            &entry("label = \"a\""),
+            &entry("label = \"a\"")
+        ));
+    }
+
+    #[test]
+    fn day_numbers_count_from_the_epoch() {
+        assert_eq!(day_number("1970-01-01"), Some(0));
+        assert_eq!(day_number("1969-12-31"), Some(-1));
+        assert_eq!(day_number("2000-03-01"), Some(11_017));
+        assert_eq!(
+            day_number("2024-03-01").zip(day_number("2024-02-28")),
+            Some((19_783, 19_781))
+        );
+        assert_eq!(
+            day_number("2023-03-01").zip(day_number("2023-02-28")),
+            Some((19_417, 19_416))
+        );
+        assert_eq!(day_number(""), None);
+        assert_eq!(day_number("2024-01"), None);
+        assert_eq!(day_number("2024-xx-01"), None);
+    }
+
+    #[test]
+    fn streaks_grow_only_on_consecutive_days() {
+        assert_eq!(next_streak("2024-01-30", "2024-01-31", 4), 5);
+        assert_eq!(next_streak("2024-01-31", "2024-02-01", 4), 5);
+        assert_eq!(next_streak("2024-02-28", "2024-02-29", 4), 5);
+        assert_eq!(next_streak("2023-12-31", "2024-01-01", 4), 5);
+        assert_eq!(next_streak("2024-01-29", "2024-01-31", 4), 1);
+        assert_eq!(next_streak("2024-02-28", "2024-03-01", 4), 1);
+        assert_eq!(next_streak("", "2024-01-31", 0), 1);
+    }
+
+    fn leveling(source: &str) -> LevelingConfig {
+        toml::from_str(source).unwrap()
+    }
+
+    #[test]
+    fn linear_thresholds_default_to_25_plus_10_per_level() {
+        let config = leveling("");
+        assert_eq!(config.threshold(1), Some(25));
+        assert_eq!(config.threshold(4), Some(55));
+        let config = leveling("base = 100\nstep = 50\n");
+        assert_eq!(config.threshold(3), Some(200));
+        let config = leveling("base = 4000000000\nstep = 4000000000\n");
+        assert_eq!(config.threshold(2), Some(u32::MAX));
+    }
+
+    #[test]
+    fn exponential_thresholds_round_and_saturate() {
+        let config = leveling("curve = \"exponential\"\n");
+        assert_eq!(config.threshold(1), Some(25));
+        assert_eq!(config.threshold(2), Some(38));
+        assert_eq!(config.threshold(3), Some(56));
+        let config = leveling("curve = \"exponential\"\nfactor = 10.0\n");
+        assert_eq!(config.threshold(40), Some(u32::MAX));
+    }
+
+    #[test]
+    fn table_thresholds_repeat_the_last_entry() {
+        let config = leveling("curve = \"table\"\nthresholds = [10, 30, 60]\n");
+        assert_eq!(config.threshold(1), Some(10));
+        assert_eq!(config.threshold(3), Some(60));
+        assert_eq!(config.threshold(9), Some(60));
+        assert_eq!(leveling("curve = \"table\"\n").threshold(2), Some(25));
+    }
+
+    #[test]
+    fn thresholds_stop_at_max_level_and_are_never_zero() {
+        let config = leveling("max_level = 3\n");
+        assert_eq!(config.threshold(2), Some(35));
+        assert_eq!(config.threshold(3), None);
+        assert_eq!(config.threshold(4), None);
+        assert_eq!(leveling("base = 0\nstep = 0\n").threshold(5), Some(1));
+        let config = leveling("curve = \"table\"\nthresholds = [0]\n");
+        assert_eq!(config.threshold(1), Some(1));
+    }
+
+    fn parse(args: &[&str]) -> Result<Cli, String> {
+        Cli::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn global_flags_take_separate_or_inline_values() {
+        let cli = parse(&["--profile", "ada", "--config=/tmp/lord.toml", "--no-color"]).unwrap();
+        assert_eq!(cli.profile.as_deref(), Some("ada"));
+        assert_eq!(cli.config, Some(PathBuf::from("/tmp/lord.toml")));
+        assert!(cli.no_color && !cli.no_splash);
+        assert!(cli.command.is_none());
+        let cli = parse(&["--start=lab", "--no-splash"]).unwrap();
+        assert_eq!(cli.start.as_deref(), Some("lab"));
+        assert!(cli.no_splash);
+    }
+
+    #[test]
+    fn subcommands_collect_their_own_flags() {
+        let cli = parse(&["journal", "--since", "2024-01-01", "--action=check_mail"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(CliCommand::Journal(JournalQuery { since: Some(ref since), until: None, action: Some(ref action) }))
+                if since == "2024-01-01" && action == "check_mail"
+        ));
+        assert!(matches!(
+            parse(&["config", "show", "--origin"]).unwrap().command,
+            Some(CliCommand::ConfigShow { origin: true })
+        ));
+        assert!(matches!(
+            parse(&["init", "--force"]).unwrap().command,
+            Some(CliCommand::Init { force: true })
+        ));
+        assert!(matches!(
+            parse(&["check-config", "lord.toml"]).unwrap().command,
+            Some(CliCommand::CheckConfig(Some(ref path))) if path == Path::new("lord.toml")
+        ));
+        assert!(matches!(
+            parse(&["--version"]).unwrap().command,
+            Some(CliCommand::Version)
+        ));
+        assert!(matches!(
+            parse(&["-h"]).unwrap().command,
+            Some(CliCommand::Help)
+        ));
+    }
+
+    #[test]
+    fn run_takes_an_action_and_an_optional_target() {
+        let cli = parse(&["--profile", "ada", "run", "chest", "Nmap Sweep"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(CliCommand::Run(RunRequest { ref action, target: Some(ref target) }))
+                if action == "chest" && target == "Nmap Sweep"
+        ));
+        assert_eq!(cli.profile.as_deref(), Some("ada"));
+        assert!(matches!(
+            parse(&["run", "mail", "--no-splash"]).unwrap().command,
+            Some(CliCommand::Run(RunRequest { ref action, target: None })) if action == "mail"
+        ));
+    }
+
+    #[test]
+    fn bad_arguments_are_rejected() {
+        let error = |args: &[&str]| parse(args).err();
+        assert_eq!(
+            error(&["run"]).as_deref(),
+            Some("run expects an action, e.g. `lord run mail`")
+        );
+        assert_eq!(
+            error(&["run", "chest", "a", "b"]).as_deref(),
+            Some("Unexpected argument: b")
+        );
+        assert_eq!(
+            error(&["--profile="]).as_deref(),
+            Some("--profile requires a name")
+        );
+        assert_eq!(
+            error(&["--profile", " "]).as_deref(),
+            Some("--profile requires a name")
+        );
+        assert_eq!(
+            error(&["--start"]).as_deref(),
+            Some("--start requires a value")
+        );
+        assert_eq!(
+            error(&["config"]).as_deref(),
+            Some("config expects a subcommand: show")
+        );
+        assert_eq!(
+            error(&["--since", "2024-01-01"]).as_deref(),
+            Some("Unrecognized argument: --since")
+        );
+        assert_eq!(
+            error(&["version", "help"]).as_deref(),
+            Some("Unrecognized argument: help")
+        );
+        assert_eq!(
+            error(&["check-config", "a", "b"]).as_deref(),
+            Some("Unrecognized argument: b")
+        );
+    }
+
+    #[test]
+    fn date_arguments_must_be_yyyy_mm_dd() {
+        let date = |value: &str| parse_date_arg("--since", value.to_string());
+        assert_eq!(date("2024-01-31").as_deref(), Ok("2024-01-31"));
+        for value in [
+            "",
+            "2024-1-31",
+            "2024/01/31",
+            "24-01-31",
+            "2024-01-31T08",
+            "２024-01-3",
+        ] {
+            assert_eq!(
+                date(value),
+                Err(format!(
+                    "--since expects a date like 2024-01-31, got {value}"
+                ))
+            );
+        }
+        assert_eq!(
+            parse(&["journal", "--until=yesterday"]).err().as_deref(),
+            Some("--until expects a date like 2024-01-31, got yesterday")
+        );
+    }
 }